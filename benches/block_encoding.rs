@@ -0,0 +1,53 @@
+//! parsing and decoding a `getBlock` response of 2000 transactions with 
+//! base58 and base64 encoded transactions
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::json;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, system_transaction};
+use solana_transaction_status::UiConfirmedBlock;
+
+use vote::block::{decode_block_transactions, BlockEncoding};
+
+const NUM_TRANSACTIONS: usize = 2000;
+
+// the raw `getBlock` result with the transactions in `encoding`
+fn block_response(encoding: BlockEncoding) -> String { 
+    let keypair = Keypair::new();
+    let transactions: Vec<_> = (0..NUM_TRANSACTIONS)
+        .map(|i| { 
+            let tx = system_transaction::transfer(&keypair, &Pubkey::new_unique(), i as u64, Hash::new_unique());
+            let tx = bincode::serialize(&tx).unwrap();
+            let encoded = match encoding { 
+                BlockEncoding::Base58 => bs58::encode(tx).into_string(),
+                BlockEncoding::Base64 => BASE64.encode(tx),
+            };
+            json!({ "transaction": [encoded, encoding.as_str()], "meta": null })
+        })
+        .collect();
+    json!({ 
+        "previousBlockhash": Hash::new_unique().to_string(),
+        "blockhash": Hash::new_unique().to_string(),
+        "parentSlot": 99,
+        "transactions": transactions,
+        "blockTime": null,
+        "blockHeight": 100,
+    }).to_string()
+}
+
+fn bench_decode(c: &mut Criterion) { 
+    let mut group = c.benchmark_group("decode_block");
+    for encoding in [BlockEncoding::Base58, BlockEncoding::Base64] { 
+        let resp = block_response(encoding);
+        // the response size is what goes over the wire
+        group.throughput(Throughput::Bytes(resp.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(encoding.as_str()), &resp, |b, resp| b.iter(|| { 
+            let block: UiConfirmedBlock = serde_json::from_str(resp).unwrap();
+            decode_block_transactions(&block).unwrap()
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
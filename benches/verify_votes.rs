@@ -0,0 +1,74 @@
+//! vote signature and PoH verification of a synthetic block of 500 votes
+
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::json;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::{Keypair, Signer}, transaction::{Transaction, VersionedTransaction}, vote::{instruction::vote, program as vote_program, state::Vote}};
+use solana_transaction_status::EntryProof;
+
+use vote::merkle::transaction_leaf_hash;
+use vote::poh::{next_hash_with_tx_hash, verify_poh_ticks};
+use vote::votes::{compute_vote_tally, verify_signatures};
+
+const NUM_VOTES: usize = 500;
+const SLOT: u64 = 100;
+
+// a vote on `SLOT` from each of `NUM_VOTES` validators, with their stakes
+fn votes() -> (Vec<VersionedTransaction>, HashMap<Pubkey, u64>) { 
+    let bank_hash = Hash::new_unique();
+    let validators = (0..NUM_VOTES).map(|_| Keypair::new()).collect::<Vec<_>>();
+    let votes = validators.iter()
+        .map(|validator| { 
+            let ix = vote(&validator.pubkey(), &validator.pubkey(), Vote::new(vec![SLOT], bank_hash));
+            Transaction::new_signed_with_payer(&[ix], Some(&validator.pubkey()), &[validator], Hash::new_unique()).into()
+        })
+        .collect();
+    let stakes = validators.iter().map(|validator| (validator.pubkey(), 1_000)).collect();
+    (votes, stakes)
+}
+
+// an entry per vote, each mixing in its leaf hash after 100 hashes. built 
+// from the entries' serde form, which names the variant
+fn entries(start_hash: Hash, votes: &[VersionedTransaction]) -> Vec<EntryProof> { 
+    let mut prev_hash = start_hash;
+    votes.iter()
+        .map(|tx| { 
+            let transaction_hash = transaction_leaf_hash(&tx.signatures[0]);
+            prev_hash = next_hash_with_tx_hash(&prev_hash, 100, Some(transaction_hash));
+            serde_json::from_value(json!({ 
+                "PartialEntry": { "num_hashes": 100, "hash": prev_hash, "transaction_hash": transaction_hash },
+            })).unwrap()
+        })
+        .collect()
+}
+
+fn bench_signatures(c: &mut Criterion) { 
+    let (votes, stakes) = votes();
+    let pubkeys = votes.iter().map(|tx| tx.message.static_account_keys()[0]).collect::<Vec<_>>();
+    let messages = votes.iter().map(|tx| tx.message.serialize()).collect::<Vec<_>>();
+    let messages = messages.iter().map(|message| &message[..]).collect::<Vec<_>>();
+    let signatures = votes.iter().map(|tx| tx.signatures[0]).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("vote_signatures");
+    group.bench_function("batch", |b| b.iter(|| verify_signatures(&pubkeys, &messages, &signatures)));
+    group.bench_function("one_at_a_time", |b| b.iter(|| { 
+        signatures.iter()
+            .zip(pubkeys.iter())
+            .zip(messages.iter())
+            .map(|((signature, pubkey), message)| signature.verify(pubkey.as_ref(), message))
+            .collect::<Vec<_>>()
+    }));
+    group.bench_function("compute_vote_tally", |b| b.iter(|| compute_vote_tally(&votes, &stakes, SLOT, &vote_program::id())));
+    group.finish();
+}
+
+fn bench_poh(c: &mut Criterion) { 
+    let (votes, _) = votes();
+    let start_hash = Hash::new_unique();
+    let entries = entries(start_hash, &votes);
+    c.bench_function("verify_poh_ticks", |b| b.iter(|| verify_poh_ticks(start_hash, &entries).unwrap()));
+}
+
+criterion_group!(benches, bench_signatures, bench_poh);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use solana_sdk::hash::{Hash, hashv};
+
+use crate::version::ClusterVersion;
+
+/// what a bank hash is computed from, as carried by a block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankHashInputs { 
+    pub parent_hash: Hash,
+    pub accounts_delta_hash: Hash,
+    pub signature_count: u64,
+    pub last_blockhash: Hash,
+    /// set on the slots whose bank mixes in the epoch accounts hash
+    pub epoch_accounts_hash: Option<Hash>,
+}
+
+/// how a runtime version computes the bank hash. the formula has changed 
+/// across solana releases, so blocks are verified with the scheme of the 
+/// cluster that produced them
+pub trait BankHashScheme: Debug + Send + Sync { 
+    fn bank_hash(&self, inputs: &BankHashInputs) -> Hash;
+}
+
+/// before 1.16: `hashv(parent_hash, accounts_delta_hash, signature_count, 
+/// last_blockhash)`, the epoch accounts hash is never mixed in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LegacyBankHash;
+
+impl BankHashScheme for LegacyBankHash { 
+    fn bank_hash(&self, inputs: &BankHashInputs) -> Hash { 
+        hashv(&[
+            inputs.parent_hash.as_ref(),
+            inputs.accounts_delta_hash.as_ref(),
+            &inputs.signature_count.to_le_bytes(),
+            inputs.last_blockhash.as_ref(),
+        ])
+    }
+}
+
+/// 1.16 on: the legacy bank hash, with the epoch accounts hash mixed in after 
+/// the other fields on the slots which include it. 
+/// 
+/// later runtimes also mix in the accounts lt hash, which `getBlockHeaders` 
+/// doesnt return, so those blocks cant be verified yet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpochAccountsHashBankHash;
+
+impl BankHashScheme for EpochAccountsHashBankHash { 
+    fn bank_hash(&self, inputs: &BankHashInputs) -> Hash { 
+        let bank_hash = LegacyBankHash.bank_hash(inputs);
+        match inputs.epoch_accounts_hash { 
+            Some(epoch_accounts_hash) => hashv(&[bank_hash.as_ref(), epoch_accounts_hash.as_ref()]),
+            None => bank_hash,
+        }
+    }
+}
+
+/// the scheme of a cluster running `version` (eg. `1.16.14`, as returned by 
+/// `getVersion`), `None` if it cant be parsed
+pub fn bank_hash_scheme_for_version(version: &str) -> Option<Box<dyn BankHashScheme>> { 
+    let version = version.parse::<ClusterVersion>().ok()?;
+    if (version.major, version.minor) < (1, 16) { 
+        Some(Box::new(LegacyBankHash))
+    } else { 
+        Some(Box::new(EpochAccountsHashBankHash))
+    }
+}
+
+/// the bank hash as computed by the runtime: 
+/// `hashv(parent_hash, accounts_delta_hash, signature_count, last_blockhash)`, 
+/// with the signature count as a little endian u64. when the bank includes 
+/// the epoch accounts hash, it is mixed in after the other fields. this is 
+/// `EpochAccountsHashBankHash`, see `BankHashScheme` for other versions
+pub fn recompute_bank_hash(
+    parent_hash: &Hash,
+    accounts_delta_hash: &Hash,
+    signature_count: u64,
+    last_blockhash: &Hash,
+    epoch_accounts_hash: Option<Hash>,
+) -> Hash { 
+    EpochAccountsHashBankHash.bank_hash(&BankHashInputs { 
+        parent_hash: *parent_hash,
+        accounts_delta_hash: *accounts_delta_hash,
+        signature_count,
+        last_blockhash: *last_blockhash,
+        epoch_accounts_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests { 
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_recompute_bank_hash() { 
+        let [parent_hash, accounts_delta_hash, last_blockhash, epoch_accounts_hash] = [1, 2, 3, 4].map(|x| Hash::new_from_array([x; 32]));
+        // sha256 of the fields, computed independently
+        let bank_hash = Hash::from_str("2SWvpdZttMinTTpnTaiD3ezXLJbaVHXjB4DHC35Gp2dT").unwrap();
+        let bank_hash_with_eah = Hash::from_str("6f9nhANSHj7gV8AaCinicB3AFbmYMysS29oSggAJTwA7").unwrap();
+
+        assert_eq!(recompute_bank_hash(&parent_hash, &accounts_delta_hash, 5, &last_blockhash, None), bank_hash);
+        assert_eq!(recompute_bank_hash(&parent_hash, &accounts_delta_hash, 5, &last_blockhash, Some(epoch_accounts_hash)), bank_hash_with_eah);
+
+        // the legacy scheme never mixes the epoch accounts hash in
+        let inputs = BankHashInputs { 
+            parent_hash,
+            accounts_delta_hash,
+            signature_count: 5,
+            last_blockhash,
+            epoch_accounts_hash: Some(epoch_accounts_hash),
+        };
+        assert_eq!(LegacyBankHash.bank_hash(&inputs), bank_hash);
+    }
+}
@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use solana_sdk::{hash::Hash, message::VersionedMessage, pubkey::Pubkey, transaction::VersionedTransaction};
+use solana_transaction_status::{EncodedTransaction, TransactionBinaryEncoding, UiConfirmedBlock, UiTransactionStatusMeta, option_serializer::OptionSerializer};
+
+use crate::error::LightNodeError;
+
+/// binary encoding of the transactions in `getBlock` responses. base64 
+/// payloads are smaller and faster to decode than base58
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockEncoding { 
+    #[default]
+    Base58,
+    Base64,
+}
+
+impl BlockEncoding { 
+    pub fn as_str(&self) -> &'static str { 
+        match self { 
+            BlockEncoding::Base58 => "base58",
+            BlockEncoding::Base64 => "base64",
+        }
+    }
+}
+
+impl FromStr for BlockEncoding { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        match s { 
+            "base58" => Ok(BlockEncoding::Base58),
+            "base64" => Ok(BlockEncoding::Base64),
+            _ => Err(format!("unknown block encoding '{}', expected 'base58' or 'base64'", s)),
+        }
+    }
+}
+
+/// the parts of a `getBlock` response a block header is checked against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSummary { 
+    pub blockhash: Hash,
+    /// blockhash of the parent block, which the block's PoH chain starts from
+    pub previous_blockhash: Hash,
+    pub parent_slot: u64,
+    /// signatures across all the block's transactions
+    pub signature_count: u64,
+}
+
+/// summarize a block fetched with binary encoded transactions
+pub fn summarize_block(block: &UiConfirmedBlock) -> Result<BlockSummary, LightNodeError> { 
+    let signature_count = decode_block_transactions(block)?
+        .iter()
+        .map(|tx| tx.signatures.len() as u64)
+        .sum();
+    Ok(BlockSummary { 
+        blockhash: Hash::from_str(&block.blockhash)?,
+        previous_blockhash: Hash::from_str(&block.previous_blockhash)?,
+        parent_slot: block.parent_slot,
+        signature_count,
+    })
+}
+
+/// decode all the transactions of a block fetched with binary (base58 or 
+/// base64) encoded transactions, eg. to scan for a program's instructions. 
+/// json encoded transactions are rejected with `UnsupportedTransactionEncoding`, 
+/// as the signed message bytes cant be recovered from them
+pub fn decode_block_transactions(block: &UiConfirmedBlock) -> Result<Vec<VersionedTransaction>, LightNodeError> { 
+    Ok(decode_block_transactions_with_meta(block)?
+        .into_iter()
+        .map(|(tx, _)| tx)
+        .collect())
+}
+
+/// `decode_block_transactions` paired with each transaction's status meta, 
+/// which holds the keys loaded from lookup tables (see `resolve_account_keys`)
+pub fn decode_block_transactions_with_meta(block: &UiConfirmedBlock) -> Result<Vec<(VersionedTransaction, Option<&UiTransactionStatusMeta>)>, LightNodeError> { 
+    block.transactions
+        .iter()
+        .flatten()
+        .map(|tx_with_meta| { 
+            let tx = decode_transaction(&tx_with_meta.transaction)?
+                .ok_or_else(|| LightNodeError::UnsupportedTransactionEncoding("json".to_string()))?;
+            Ok((tx, tx_with_meta.meta.as_ref()))
+        })
+        .collect()
+}
+
+/// decode a binary encoded transaction from a `getBlock` or `getTransaction` 
+/// response. json encoded transactions cant be verified so they are skipped 
+/// (`None`), callers needing every transaction reject them
+pub fn decode_transaction(encoded: &EncodedTransaction) -> Result<Option<VersionedTransaction>, LightNodeError> { 
+    let tx_bytes = match encoded { 
+        EncodedTransaction::LegacyBinary(tx) 
+        | EncodedTransaction::Binary(tx, TransactionBinaryEncoding::Base58) => bs58::decode(tx).into_vec()?,
+        EncodedTransaction::Binary(tx, TransactionBinaryEncoding::Base64) => BASE64.decode(tx)?,
+        EncodedTransaction::Json(_) => return Ok(None),
+        EncodedTransaction::Accounts(_) => { 
+            return Err(LightNodeError::UnsupportedTransactionEncoding("accounts".to_string()));
+        }
+    };
+    let tx: VersionedTransaction = bincode::deserialize(&tx_bytes[..])?;
+    Ok(Some(tx))
+}
+
+/// the message's static account keys followed by the writable and readonly 
+/// keys loaded from address lookup tables, which is the order instruction 
+/// account indices refer to
+pub fn resolve_account_keys(msg: &VersionedMessage, meta: Option<&UiTransactionStatusMeta>) -> Result<Vec<Pubkey>, LightNodeError> { 
+    let mut account_keys = msg.static_account_keys().to_vec();
+    if let Some(OptionSerializer::Some(loaded)) = meta.map(|meta| meta.loaded_addresses.as_ref()) { 
+        for key in loaded.writable.iter().chain(loaded.readonly.iter()) { 
+            account_keys.push(Pubkey::from_str(key)?);
+        }
+    }
+    Ok(account_keys)
+}
+
+#[cfg(test)]
+mod tests { 
+    use serde_json::json;
+    use solana_sdk::{signature::Keypair, system_transaction};
+
+    use super::*;
+
+    fn block_with(transactions: Vec<serde_json::Value>) -> UiConfirmedBlock { 
+        let transactions: Vec<_> = transactions.into_iter()
+            .map(|transaction| json!({ "transaction": transaction, "meta": null }))
+            .collect();
+        serde_json::from_value(json!({ 
+            "previousBlockhash": Hash::new_unique().to_string(),
+            "blockhash": Hash::new_unique().to_string(),
+            "parentSlot": 1,
+            "transactions": transactions,
+            "blockTime": null,
+            "blockHeight": null,
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_json_encoded_block_is_rejected() { 
+        let keypair = Keypair::new();
+        let tx = system_transaction::transfer(&keypair, &Pubkey::new_unique(), 1, Hash::new_unique());
+        let json_tx = json!({ 
+            "signatures": [tx.signatures[0].to_string()],
+            "message": { 
+                "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": 1 },
+                "accountKeys": tx.message.account_keys.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+                "recentBlockhash": tx.message.recent_blockhash.to_string(),
+                "instructions": [],
+            },
+        });
+        let block = block_with(vec![json_tx]);
+        assert!(matches!(decode_block_transactions(&block), Err(LightNodeError::UnsupportedTransactionEncoding(_))));
+        assert_eq!(decode_transaction(&block.transactions.unwrap()[0].transaction).unwrap(), None);
+    }
+}
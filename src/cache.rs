@@ -0,0 +1,75 @@
+use std::{path::PathBuf, time::{Duration, SystemTime}};
+
+use solana_sdk::signature::Signature;
+use tracing::{debug, warn};
+
+/// on-disk cache of raw `getBlockHeaders` results, one file per 
+/// `(slot, signature)`
+#[derive(Debug, Clone)]
+pub struct BlockHeaderCache { 
+    dir: PathBuf,
+    /// entries older than this are refetched, `None` keeps them forever
+    ttl: Option<Duration>,
+}
+
+impl BlockHeaderCache { 
+    pub fn new(dir: impl Into<PathBuf>) -> Self { 
+        Self { dir: dir.into(), ttl: None }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self { 
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn dir(&self) -> &PathBuf { 
+        &self.dir
+    }
+
+    fn path(&self, slot: u64, signature: &Signature) -> PathBuf { 
+        self.dir.join(format!("{}-{}.bin", slot, signature))
+    }
+
+    /// the cached headers, if present and not expired
+    pub async fn get(&self, slot: u64, signature: &Signature) -> Option<Vec<u8>> { 
+        let path = self.path(slot, signature);
+        let metadata = tokio::fs::metadata(&path).await.ok()?;
+        if let Some(ttl) = self.ttl { 
+            let age = metadata.modified().ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .unwrap_or_default();
+            if age > ttl { 
+                debug!(slot, %signature, "cached block headers expired");
+                return None;
+            }
+        }
+        let bytes = tokio::fs::read(&path).await.ok()?;
+        debug!(slot, %signature, "block headers cache hit");
+        Some(bytes)
+    }
+
+    /// store the headers, a failed write only means a later cache miss. 
+    /// written through a temporary file, so an interrupted write isnt read 
+    /// back as truncated headers
+    pub async fn put(&self, slot: u64, signature: &Signature, bytes: &[u8]) { 
+        let path = self.path(slot, signature);
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let result = async { 
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(&tmp_path, bytes).await?;
+            tokio::fs::rename(&tmp_path, &path).await
+        }.await;
+        if let Err(e) = result { 
+            warn!(slot, %signature, "failed to cache block headers: {}", e);
+        }
+    }
+
+    /// remove every cached entry
+    pub async fn clear(&self) -> std::io::Result<()> { 
+        match tokio::fs::remove_dir_all(&self.dir).await { 
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature};
+
+use vote::block::BlockEncoding;
+use vote::error::LightNodeError;
+use vote::forks::ForkEvent;
+use vote::selftest::{Check, SelfTestReport};
+use vote::verify::{StageTimings, TxInclusionProof, VerificationReport};
+use vote::version::VersionCheck;
+use vote::votes::{SupermajorityStake, SupermajorityThreshold};
+
+/// the tx isnt included in the block headers (or wasnt found)
+pub const EXIT_TX_NOT_INCLUDED: i32 = 1;
+/// the entries dont form a valid PoH chain
+pub const EXIT_POH_FAILED: i32 = 2;
+/// the header doesnt match `getBlock`, or the votes are for another bank hash
+pub const EXIT_BANK_HASH_MISMATCH: i32 = 3;
+/// the bank hash doesnt have a supermajority of the votes
+pub const EXIT_NO_SUPERMAJORITY: i32 = 4;
+/// an rpc or transport error, eg. a timeout or an unavailable block
+pub const EXIT_RPC_ERROR: i32 = 5;
+/// any other error, eg. an invalid keypair or header file
+pub const EXIT_OTHER_ERROR: i32 = 6;
+
+const EXIT_CODES_HELP: &str = "EXIT CODES:
+    0    verified
+    1    tx not included in the block (or never found, eg. dropped)
+    2    PoH verification failed
+    3    bank hash mismatch (header doesnt match the block, or votes are for another bank hash)
+    4    no supermajority of votes for the bank hash
+    5    rpc or transport error
+    6    other error";
+
+#[derive(Debug, Parser)]
+#[clap(about = "solana light node: verify transaction inclusion and bank hash votes")]
+#[clap(after_help = EXIT_CODES_HELP)]
+pub struct Cli { 
+    /// toml file of settings (endpoints, commitment, vote window, keypairs 
+    /// and retry policy), which flags override
+    #[clap(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// rpc endpoint (must support the getBlockHeaders method), defaults to 
+    /// the local validator's
+    #[clap(long, global = true)]
+    pub endpoint: Option<String>,
+
+    /// websocket endpoint for slot subscriptions, derived from the rpc 
+    /// endpoint when not set
+    #[clap(long, global = true)]
+    pub ws_endpoint: Option<String>,
+
+    /// yellowstone geyser grpc endpoint to stream the blocks scanned for 
+    /// votes from, instead of polling them over rpc
+    #[cfg(feature = "geyser")]
+    #[clap(long, global = true)]
+    pub geyser_endpoint: Option<String>,
+
+    /// `x-token` for the geyser endpoint, better set as `geyser_x_token` in 
+    /// the config file
+    #[cfg(feature = "geyser")]
+    #[clap(long, global = true)]
+    pub geyser_x_token: Option<String>,
+
+    /// commitment level used when fetching blocks and transactions, 
+    /// defaults to `confirmed`
+    #[clap(long, global = true)]
+    pub commitment: Option<CommitmentConfig>,
+
+    /// only verify against finalized transactions and blocks, and only count 
+    /// votes from finalized blocks
+    #[clap(long, global = true)]
+    pub finalized_only: bool,
+
+    /// stake the supermajority is checked against: `total` (including 
+    /// delinquent validators) or `current` (only validators which are voting)
+    #[clap(long, global = true, default_value = "total")]
+    pub supermajority_stake: SupermajorityStake,
+
+    /// share of the stake the bank hash needs for a supermajority, eg. `3/4` 
+    /// for a stricter check than the cluster's
+    #[clap(long, global = true, default_value = "2/3")]
+    pub supermajority_threshold: SupermajorityThreshold,
+
+    /// number of slots after the slot scanned for votes, defaults to 5
+    #[clap(long, global = true)]
+    pub vote_window: Option<u64>,
+
+    /// stop scanning for votes once the bank hash has a supermajority, 
+    /// scanning up to `--vote-window` slots
+    #[clap(long, global = true)]
+    pub adaptive_vote_window: bool,
+
+    /// program scanned for votes, for clusters with the vote program at a 
+    /// nonstandard address
+    #[clap(long, global = true)]
+    pub vote_program_id: Option<Pubkey>,
+
+    /// when the cluster version isnt known to work: `warn`, `error` (fail 
+    /// verification) or `off` (dont check)
+    #[clap(long, global = true, default_value = "warn")]
+    pub version_check: VersionCheck,
+
+    /// max rpc requests in flight at once, lower it if the endpoint rate 
+    /// limits (http 429)
+    #[clap(long, global = true, default_value = "16")]
+    pub max_connections: usize,
+
+    /// give up on verifying a transaction after this many seconds
+    #[clap(long, global = true)]
+    pub verify_timeout: Option<u64>,
+
+    /// encoding of the transactions in fetched blocks: `base58` or `base64` 
+    /// (smaller and faster to decode)
+    #[clap(long, global = true, default_value = "base58")]
+    pub block_encoding: BlockEncoding,
+
+    /// directory to cache fetched block headers in
+    #[clap(long, global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// save every rpc response to this directory, for `--replay-dir`
+    #[clap(long, global = true)]
+    pub record_dir: Option<PathBuf>,
+
+    /// verify offline from the rpc responses saved in this directory by 
+    /// `--record-dir`, instead of requesting them
+    #[clap(long, global = true, conflicts_with = "record-dir")]
+    pub replay_dir: Option<PathBuf>,
+
+    /// output format: `text` or `json` (one object per line, hashes as 
+    /// base58 strings)
+    #[clap(long, global = true, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// exit with 0 when the bank hash doesnt have a supermajority of the 
+    /// votes yet, eg. for an early signal at `processed` or `confirmed`, 
+    /// reporting the stake ratio so far as the confidence
+    #[clap(long, global = true)]
+    pub allow_unconfirmed: bool,
+
+    /// print how long each verification stage took with the text report
+    #[clap(long, short, global = true)]
+    pub verbose: bool,
+
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat { 
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        match s { 
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown format '{}', expected 'text' or 'json'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command { 
+    /// verify a transaction against the block headers of a known slot
+    VerifySlot { 
+        #[clap(long)]
+        slot: u64,
+        #[clap(long)]
+        signature: Signature,
+        /// print the block header's entries to stderr before verifying
+        #[clap(long)]
+        dump_entries: bool,
+    },
+    /// look up the slot of a confirmed transaction and verify it
+    VerifyTx { 
+        #[clap(long)]
+        signature: Signature,
+    },
+    /// verify a transaction's inclusion and its slot's bank hash, without 
+    /// waiting for votes
+    VerifyInclusion { 
+        #[clap(long)]
+        signature: Signature,
+    },
+    /// verify the inclusion of the transaction at a position in the block 
+    /// (in `getBlock` order) in an entry of the block headers
+    VerifyInclusionByIndex { 
+        #[clap(long)]
+        slot: u64,
+        /// index of the entry in the block headers
+        #[clap(long)]
+        entry_index: usize,
+        /// index of the transaction in the block
+        #[clap(long)]
+        tx_index: usize,
+    },
+    /// verify a transaction against block headers obtained elsewhere, without 
+    /// fetching anything or checking votes
+    VerifyHeader { 
+        #[clap(long)]
+        slot: u64,
+        #[clap(long)]
+        signature: Signature,
+        /// bincode serialized headers, as returned by `getBlockHeaders`
+        #[clap(long)]
+        header_file: PathBuf,
+        /// epoch accounts hash mixed into the slot's bank hash, for the slot 
+        /// 3/4 into an epoch which includes it
+        #[clap(long)]
+        epoch_accounts_hash: Option<Hash>,
+        /// the block's signature count (eg. from `getBlock`), checked against 
+        /// the header's before the bank hash is recomputed from it
+        #[clap(long)]
+        signature_count: Option<u64>,
+        /// print the block header's entries to stderr before verifying
+        #[clap(long)]
+        dump_entries: bool,
+    },
+    /// verify each new finalized slot as it is produced
+    Follow { 
+        /// slot to start from, defaults to the current finalized slot
+        #[clap(long)]
+        start_slot: Option<u64>,
+        /// json file recording the last verified slot, which a restarted 
+        /// follow resumes after (unless `--start-slot` is given)
+        #[clap(long)]
+        state_file: Option<PathBuf>,
+        /// serve prometheus metrics of the verified slots at 
+        /// `http://<ADDR>/metrics`, eg. `0.0.0.0:9100`
+        #[cfg(feature = "metrics")]
+        #[clap(long)]
+        metrics_addr: Option<std::net::SocketAddr>,
+    },
+    /// check the endpoint supports what verification needs (getBlockHeaders, 
+    /// PoH and bank hash verification, vote scanning) on a recent slot
+    Selftest,
+    /// report the slots in a range whose votes were split between bank 
+    /// hashes, with the validators voting for each
+    DetectForks { 
+        #[clap(long)]
+        start_slot: u64,
+        /// last slot scanned (inclusive)
+        #[clap(long)]
+        end_slot: u64,
+    },
+    /// send a transfer between two local validator keypairs and verify it
+    Demo { 
+        /// sender keypair file, defaults to the local validator's identity
+        #[clap(long)]
+        keypair: Option<PathBuf>,
+        /// sender keypair as a base58 string, used instead of `--keypair` 
+        /// (as is the `SOL_LIGHTNODE_KEYPAIR` env var)
+        #[clap(long)]
+        keypair_base58: Option<String>,
+        /// recipient keypair file
+        #[clap(long)]
+        recipient: Option<PathBuf>,
+    },
+}
+
+pub fn print_inclusion_proof(proof: &TxInclusionProof) { 
+    println!("slot: {}", proof.slot);
+    println!("signature: {}", proof.signature);
+    println!("tx included: {}", proof.tx_included());
+    println!("poh verified: {}", proof.poh_verified);
+    if let Some(bank_hash) = proof.bank_hash { 
+        println!("bank hash: {}", bank_hash);
+    }
+}
+
+pub fn print_fork_event(fork_event: &ForkEvent) { 
+    println!("slot {}: votes split between {} bank hashes ({} of {} stakes collected)", fork_event.slot, fork_event.sides.len(), fork_event.collected_stake, fork_event.total_stake);
+    for side in fork_event.sides.iter() { 
+        println!("  {} stakes: {} validators: {}", side.bank_hash, side.stake, side.validators.len());
+        for validator in side.validators.iter() { 
+            println!("    {} stake: {}", validator.vote_account, validator.stake);
+        }
+    }
+}
+
+pub fn print_self_test(report: &SelfTestReport) { 
+    let print_check = |name: &str, check: &Check| match check { 
+        Check::Passed => println!("{}: ok", name),
+        Check::Failed(reason) => println!("{}: failed ({})", name, reason),
+        Check::Skipped => println!("{}: skipped", name),
+    };
+    println!("cluster version: {}", report.cluster_version.as_deref().unwrap_or("unknown"));
+    print_check("supported version", &report.version);
+    if let Some(slot) = report.slot { 
+        println!("slot: {}", slot);
+    }
+    print_check("getBlockHeaders", &report.block_headers);
+    print_check("poh verification", &report.poh);
+    print_check("vote scan", &report.vote_scan);
+    print_check("bank hash", &report.bank_hash);
+    println!("{}", if report.passed() { "ready to verify" } else { "not ready to verify" });
+}
+
+pub fn print_report(report: &VerificationReport, verbose: bool) { 
+    println!("slot: {}", report.slot);
+    if let Some(signature) = report.signature { 
+        println!("signature: {}", signature);
+    }
+    if let Some(cluster_version) = &report.cluster_version { 
+        println!("cluster version: {}", cluster_version);
+    }
+    println!("tx included: {}", report.tx_included);
+    println!("poh verified: {}", report.poh_verified);
+    println!("blockhash: {}", report.blockhash);
+    println!("bank hash: {}", report.bank_hash);
+    println!("bankhash vote stakes: {} total stakes: {}", report.voted_stake, report.total_stake);
+    println!("current stakes: {} delinquent stakes: {}", report.current_total_stake, report.delinquent_stake);
+    if report.stake_epoch != report.slot_epoch { 
+        println!("stakes are from epoch {}, slot is in epoch {}", report.stake_epoch, report.slot_epoch);
+    }
+    if report.unattributed_votes > 0 { 
+        println!("votes with unknown stake: {}", report.unattributed_votes);
+    }
+    if !report.signature_verification_failures.is_empty() { 
+        println!("votes with invalid signatures: {:?}", report.signature_verification_failures);
+    }
+    if !report.skipped_slots.is_empty() { 
+        println!("skipped slots scanned for votes: {:?}", report.skipped_slots);
+    }
+    if !report.unavailable_slots.is_empty() { 
+        println!("slots still unavailable when scanned for votes: {:?}", report.unavailable_slots);
+    }
+    println!("slots scanned for votes: {} stakes collected: {}", report.slots_scanned, report.collected_stake);
+    if let Some(commitment) = report.commitment { 
+        println!("commitment: {}", commitment);
+    }
+    if !report.competing_bank_hashes.is_empty() { 
+        println!("competing bank hashes:");
+        for (bank_hash, stake) in report.competing_bank_hashes.iter() { 
+            println!("  {} stakes: {}", bank_hash, stake);
+        }
+    }
+    println!("voted stake ratio: {:.4} (threshold {})", report.voted_stake_ratio, report.supermajority_threshold);
+    println!("bankhash has supermajority of votes: {} (of {} stakes)", report.is_supermajority, report.threshold_stake);
+    println!("confidence: {}", report.confidence);
+    if verbose { 
+        print_timings(&report.timings);
+    }
+}
+
+fn print_timings(timings: &StageTimings) { 
+    println!("timings:");
+    println!("  fetch transaction: {:?}", timings.fetch_transaction);
+    println!("  fetch block headers: {:?}", timings.fetch_block_headers);
+    println!("  verify merkle: {:?}", timings.verify_merkle);
+    println!("  verify poh: {:?}", timings.verify_poh);
+    println!("  fetch block: {:?}", timings.fetch_block);
+    println!("  scan votes: {:?}", timings.scan_votes);
+    println!("  total: {:?}", timings.total());
+}
+
+pub fn print_json<T: Serialize>(value: &T) { 
+    match serde_json::to_string(value) { 
+        Ok(json) => println!("{}", json),
+        Err(e) => println!("{}", serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+/// print a failed verification to stderr, as `{"error": ...}` in json
+pub fn print_error(context: &str, error: &LightNodeError, format: OutputFormat) { 
+    match format { 
+        OutputFormat::Text => eprintln!("{}: {}", context, error),
+        OutputFormat::Json => eprintln!("{}", serde_json::json!({ "error": error.to_string() })),
+    }
+}
+
+/// exit code for a finished verification, 0 when everything verified. 
+/// `checks_votes` is false for commands which dont scan votes, and with 
+/// `allow_unconfirmed` a bank hash without a supermajority (yet) passes
+pub fn report_exit_code(report: &VerificationReport, checks_votes: bool, allow_unconfirmed: bool) -> i32 { 
+    if !report.tx_included { 
+        EXIT_TX_NOT_INCLUDED
+    } else if !report.poh_verified { 
+        EXIT_POH_FAILED
+    } else if !checks_votes { 
+        0
+    } else if report.votes_for_other_bank_hash() { 
+        EXIT_BANK_HASH_MISMATCH
+    } else if !report.is_supermajority && !allow_unconfirmed { 
+        EXIT_NO_SUPERMAJORITY
+    } else { 
+        0
+    }
+}
+
+pub fn inclusion_proof_exit_code(proof: &TxInclusionProof) -> i32 { 
+    if !proof.tx_included() { 
+        EXIT_TX_NOT_INCLUDED
+    } else if !proof.poh_verified { 
+        EXIT_POH_FAILED
+    } else { 
+        0
+    }
+}
+
+pub fn error_exit_code(error: &LightNodeError) -> i32 { 
+    match error { 
+        // getTransaction never found the tx
+        LightNodeError::TxNotIncluded { .. } 
+        | LightNodeError::TransactionUnavailable { .. } 
+        | LightNodeError::TransactionDropped { .. } 
+        | LightNodeError::TransactionExpired { .. } 
+        | LightNodeError::TransactionFailed { .. } => EXIT_TX_NOT_INCLUDED,
+        LightNodeError::Poh(_) | LightNodeError::EmptyBlockHeader => EXIT_POH_FAILED,
+        LightNodeError::BlockhashMismatch { .. }
+        | LightNodeError::StartBlockhashMismatch { .. }
+        | LightNodeError::SignatureCountMismatch { .. } => EXIT_BANK_HASH_MISMATCH,
+        LightNodeError::BlockUnavailable { .. }
+        | LightNodeError::VerifyTimeout { .. }
+        | LightNodeError::BlockHeadersUnsupported { .. }
+        | LightNodeError::NoEndpoints { .. }
+        | LightNodeError::SlotSkipped { .. }
+        | LightNodeError::BlockMissing { .. } => EXIT_RPC_ERROR,
+        e if e.is_transient() => EXIT_RPC_ERROR,
+        _ => EXIT_OTHER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use solana_transaction_status::{BlockHeader, EntryProof};
+    use vote::verify::verify_block_header;
+
+    use super::*;
+
+    // a report whose tx is included and PoH verified, with the votes unchecked
+    fn verified_report() -> VerificationReport { 
+        let start_blockhash = Hash::new_unique();
+        let tick: EntryProof = serde_json::from_value(serde_json::json!({ 
+            "PartialEntry": { "num_hashes": 0, "hash": start_blockhash, "transaction_hash": null },
+        })).unwrap();
+        let block_header = BlockHeader { 
+            start_blockhash,
+            parent_hash: Hash::new_unique(),
+            accounts_delta_hash: Hash::new_unique(),
+            signature_count_buf: 0u64.to_le_bytes(),
+            entries: vec![tick],
+        };
+        let mut report = verify_block_header(5, &block_header, None, None, Signature::new_unique()).unwrap();
+        assert!(report.poh_verified);
+        report.tx_included = true;
+        report
+    }
+
+    #[test]
+    fn test_votes_for_a_single_other_bank_hash_are_a_mismatch() { 
+        let mut report = verified_report();
+        report.total_stake = 100;
+        report.threshold_stake = 100;
+        // every vote is for the same other bank hash, so nothing is competing
+        report.collected_stake = 100;
+        assert_eq!(report_exit_code(&report, true, false), EXIT_BANK_HASH_MISMATCH);
+        assert_eq!(report_exit_code(&report, true, true), EXIT_BANK_HASH_MISMATCH);
+        assert_eq!(report_exit_code(&report, false, false), 0);
+
+        // without any votes there is nothing to mismatch
+        report.collected_stake = 0;
+        assert_eq!(report_exit_code(&report, true, false), EXIT_NO_SUPERMAJORITY);
+
+        report.voted_stake = 100;
+        report.collected_stake = 100;
+        report.is_supermajority = true;
+        assert_eq!(report_exit_code(&report, true, false), 0);
+    }
+}
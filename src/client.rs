@@ -0,0 +1,1433 @@
+use std::{str::FromStr, collections::HashMap, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use futures::{stream, StreamExt};
+use serde::{Deserializer, Serialize, Deserialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{signature::Signature, transaction::VersionedTransaction, commitment_config::{CommitmentConfig, CommitmentLevel}, epoch_schedule::EpochSchedule, pubkey::Pubkey};
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+use solana_transaction_status::{UiConfirmedBlock, EncodedConfirmedTransactionWithStatusMeta, TransactionStatus};
+use solana_sdk::hash::Hash;
+
+use crate::bank_hash::{bank_hash_scheme_for_version, BankHashScheme, EpochAccountsHashBankHash};
+use crate::block::{decode_block_transactions_with_meta, decode_transaction, resolve_account_keys, summarize_block, BlockEncoding, BlockSummary};
+use crate::cache::BlockHeaderCache;
+use crate::error::LightNodeError;
+#[cfg(feature = "geyser")]
+use crate::geyser::GeyserConfig;
+use crate::replay::ReplayTransport;
+use crate::retry::{retry, RetryPolicy};
+use crate::transport::{FailoverTransport, HttpTransport, RpcEndpoint, RpcTransport, DEFAULT_CONNECT_TIMEOUT, DEFAULT_MAX_CONNECTIONS};
+use crate::version::{ClusterVersion, VersionCheck, MAX_SUPPORTED_VERSION, MIN_SUPPORTED_VERSION};
+use crate::votes::{bank_hash_stake, verify_vote_candidates, vote_candidate, SupermajorityStake, SupermajorityThreshold, VoteCandidate, VoteCounter, VoteProgress, VoteTally, VoteWindow};
+
+pub const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:8002";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_BLOCK_FETCH_CONCURRENCY: usize = 4;
+const DEFAULT_VOTE_ACCOUNTS_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// id of a json-rpc response, echoed from the request. gateways may 
+/// replace the numeric ids of requests with strings
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcId { 
+    Num(i64),
+    Str(String),
+}
+
+/// a json-rpc response: the result, or the error the node answered with
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(
+    try_from = "RawJsonRpcResponse<T>",
+    bound(deserialize = "T: Deserialize<'de>")
+)]
+pub enum JsonRpcResponse<T> { 
+    Result { jsonrpc: String, result: T, id: RpcId },
+    /// `id` is null when the node couldnt parse the request
+    Error { jsonrpc: String, error: JsonRpcError, id: Option<RpcId> },
+}
+
+/// the error object of a json-rpc response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError { 
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+impl<T> JsonRpcResponse<T> { 
+    /// the result, or the error as `LightNodeError::JsonRpc`
+    pub fn into_result(self) -> Result<T, LightNodeError> { 
+        match self { 
+            JsonRpcResponse::Result { result, .. } => Ok(result),
+            JsonRpcResponse::Error { error, .. } => Err(error.into()),
+        }
+    }
+}
+
+impl From<JsonRpcError> for LightNodeError { 
+    fn from(error: JsonRpcError) -> Self { 
+        LightNodeError::JsonRpc { code: error.code, message: error.message }
+    }
+}
+
+// deserialized as a struct rather than an untagged enum, so large results 
+// (eg. blocks) arent buffered before being parsed
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+struct RawJsonRpcResponse<T> { 
+    jsonrpc: String,
+    // a null result is `Some` of a nullable `T`, a missing one `None`
+    #[serde(default = "Option::default", deserialize_with = "deserialize_present")]
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+    id: Option<RpcId>,
+}
+
+fn deserialize_present<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> { 
+    T::deserialize(deserializer).map(Some)
+}
+
+impl<T> TryFrom<RawJsonRpcResponse<T>> for JsonRpcResponse<T> { 
+    type Error = String;
+
+    fn try_from(raw: RawJsonRpcResponse<T>) -> Result<Self, String> { 
+        match (raw.error, raw.result, raw.id) { 
+            (Some(error), _, id) => Ok(JsonRpcResponse::Error { jsonrpc: raw.jsonrpc, error, id }),
+            (None, Some(result), Some(id)) => Ok(JsonRpcResponse::Result { jsonrpc: raw.jsonrpc, result, id }),
+            (None, Some(_), None) => Err("json-rpc response has a result but no id".to_string()),
+            (None, None, _) => Err("json-rpc response has neither a result nor an error".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBlockResponse {
+    pub jsonrpc: String,
+    pub result: UiConfirmedBlock,
+    pub id: RpcId,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBlockHeadersResponse {
+    pub jsonrpc: String,
+    pub result: Vec<u8>,
+    pub id: RpcId,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionResponse {
+    pub jsonrpc: String,
+    pub result: EncodedConfirmedTransactionWithStatusMeta,
+    pub id: RpcId,
+}
+
+impl GetTransactionResponse { 
+    /// the decoded transaction, `None` when it was fetched json encoded
+    pub fn transaction(&self) -> Result<Option<VersionedTransaction>, LightNodeError> { 
+        decode_transaction(&self.result.transaction.transaction)
+    }
+}
+
+// json-rpc error codes returned when a slot has no block
+// -32007: slot was skipped, or missing due to ledger jump to recent snapshot
+// -32009: slot was skipped, or missing in long-term storage
+const SLOT_SKIPPED_ERROR_CODES: [i64; 2] = [-32007, -32009];
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+// widest slot range a getBlocks request accepts
+pub(crate) const MAX_GET_BLOCKS_RANGE: u64 = 500_000;
+const BALANCE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// activated stake per vote account, as fetched during `epoch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteAccountStakes { 
+    pub epoch: u64,
+    pub total_stake: u128,
+    /// stake of the validators which are currently voting
+    pub current_total_stake: u128,
+    pub delinquent_stake: u128,
+    pub stakes: HashMap<Pubkey, u64>,
+}
+
+impl VoteAccountStakes { 
+    /// stakes tracked elsewhere (eg. by an indexer) for `epoch`, keyed by 
+    /// vote account. which validators are delinquent isnt known, so all the 
+    /// stake counts as current
+    pub fn from_stakes(epoch: u64, stakes: HashMap<Pubkey, u64>) -> Self { 
+        let total_stake = stakes.values().map(|stake| u128::from(*stake)).sum();
+        Self { 
+            epoch,
+            total_stake,
+            current_total_stake: total_stake,
+            delinquent_stake: 0,
+            stakes,
+        }
+    }
+}
+
+struct CachedVoteAccountStakes { 
+    stakes: Arc<VoteAccountStakes>,
+    fetched_at: Instant,
+}
+
+// the votes `scan_votes` found in a range of slots, each with the slot of 
+// its block
+pub(crate) struct ScannedVotes { 
+    // in slot order
+    pub(crate) verified: Vec<(u64, VoteCandidate)>,
+    // vote accounts whose vote's signatures didnt verify
+    pub(crate) signature_verification_failures: Vec<(u64, Pubkey)>,
+    pub(crate) skipped_slots: Vec<u64>,
+    // pending slots whose block was still unavailable after retrying
+    pub(crate) unavailable_slots: Vec<u64>,
+}
+
+/// rpc client for the light node: holds the endpoint, the transport and rpc 
+/// client so connections are reused across requests 
+pub struct LightClient<T = HttpTransport> { 
+    endpoint: String,
+    ws_endpoint: Option<String>,
+    transport: T,
+    rpc_client: Option<RpcClient>,
+    timeout: Duration,
+    connect_timeout: Duration,
+    max_connections: usize,
+    commitment: CommitmentConfig,
+    finalized_only: bool,
+    block_fetch_concurrency: usize,
+    vote_accounts_ttl: Duration,
+    supermajority_stake: SupermajorityStake,
+    supermajority_threshold: SupermajorityThreshold,
+    vote_window: VoteWindow,
+    vote_program_id: Pubkey,
+    bank_hash_scheme: Arc<dyn BankHashScheme>,
+    // keyed by the slot whose bank mixes it in
+    epoch_accounts_hashes: HashMap<u64, Hash>,
+    version_check: VersionCheck,
+    cluster_version: OnceCell<String>,
+    retry_policy: RetryPolicy,
+    block_header_cache: Option<BlockHeaderCache>,
+    follow_state_file: Option<PathBuf>,
+    block_encoding: BlockEncoding,
+    #[cfg(feature = "geyser")]
+    geyser: Option<GeyserConfig>,
+    vote_accounts_cache: Mutex<Option<CachedVoteAccountStakes>>,
+    // doesnt change for the lifetime of a cluster
+    epoch_schedule: OnceCell<EpochSchedule>,
+}
+
+impl LightClient { 
+    pub fn new(endpoint: impl Into<String>) -> Self { 
+        let endpoint = endpoint.into();
+        let timeout = DEFAULT_TIMEOUT;
+        let commitment = CommitmentConfig::confirmed();
+        Self { 
+            transport: HttpTransport::new(endpoint.clone(), timeout),
+            rpc_client: Some(RpcClient::new_with_timeout_and_commitment(endpoint.clone(), timeout, commitment)),
+            endpoint,
+            ws_endpoint: None,
+            timeout,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            commitment,
+            finalized_only: false,
+            block_fetch_concurrency: DEFAULT_BLOCK_FETCH_CONCURRENCY,
+            vote_accounts_ttl: DEFAULT_VOTE_ACCOUNTS_TTL,
+            supermajority_stake: SupermajorityStake::default(),
+            supermajority_threshold: SupermajorityThreshold::default(),
+            vote_window: VoteWindow::default(),
+            vote_program_id: solana_sdk::vote::program::id(),
+            bank_hash_scheme: Arc::new(EpochAccountsHashBankHash),
+            epoch_accounts_hashes: HashMap::new(),
+            version_check: VersionCheck::default(),
+            cluster_version: OnceCell::new(),
+            retry_policy: RetryPolicy::default(),
+            block_header_cache: None,
+            follow_state_file: None,
+            block_encoding: BlockEncoding::default(),
+            #[cfg(feature = "geyser")]
+            geyser: None,
+            vote_accounts_cache: Mutex::new(None),
+            epoch_schedule: OnceCell::new(),
+        }
+    }
+
+    /// timeout of each rpc request, defaults to 30s
+    pub fn with_timeout(mut self, timeout: Duration) -> Self { 
+        self.timeout = timeout;
+        self.transport = self.http_transport();
+        self.rpc_client = Some(RpcClient::new_with_timeout_and_commitment(self.endpoint.clone(), timeout, self.commitment));
+        self
+    }
+
+    /// how long to wait for a connection to the endpoint, defaults to 5s
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self { 
+        self.connect_timeout = connect_timeout;
+        self.transport = self.http_transport();
+        self
+    }
+
+    /// max rpc requests in flight to each endpoint at once, defaults to 16. 
+    /// lower it for rate limited public rpcs
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self { 
+        self.max_connections = max_connections.max(1);
+        self.transport = self.http_transport();
+        self
+    }
+
+    fn http_transport(&self) -> HttpTransport { 
+        HttpTransport::new(self.endpoint.clone(), self.timeout)
+            .with_connect_timeout(self.connect_timeout)
+            .with_max_connections(self.max_connections)
+    }
+
+    /// replay the rpc responses saved in `replay_dir` (see `ReplayTransport`) 
+    /// instead of requesting them, to rerun a verification offline. the rpc 
+    /// client, used by `follow` and `self_test`, still goes to the endpoint
+    pub fn with_replay_dir(self, replay_dir: impl Into<PathBuf>) -> LightClient<ReplayTransport> { 
+        self.with_transport(ReplayTransport::new(replay_dir))
+    }
+
+    /// save every rpc response to `record_dir` as it is fetched, for 
+    /// `with_replay_dir` to replay
+    pub fn with_record_dir(self, record_dir: impl Into<PathBuf>) -> LightClient<ReplayTransport> { 
+        let transport = ReplayTransport::recording(record_dir, self.http_transport());
+        self.with_transport(transport)
+    }
+
+    /// fail over between `endpoints` in priority order. `endpoint` (used by 
+    /// the rpc client and for subscriptions) stays as it was
+    pub fn with_endpoints(self, endpoints: impl IntoIterator<Item = impl Into<RpcEndpoint>>) -> LightClient<FailoverTransport> { 
+        let transport = FailoverTransport::new(endpoints, self.timeout)
+            .with_connect_timeout(self.connect_timeout)
+            .with_max_connections(self.max_connections);
+        self.with_transport(transport)
+    }
+}
+
+impl<T: RpcTransport> LightClient<T> { 
+    /// send the verification requests through `transport` instead, eg. 
+    /// to serve them from fixtures
+    pub fn with_transport<U: RpcTransport>(self, transport: U) -> LightClient<U> { 
+        LightClient { 
+            endpoint: self.endpoint,
+            ws_endpoint: self.ws_endpoint,
+            transport,
+            rpc_client: self.rpc_client,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            max_connections: self.max_connections,
+            commitment: self.commitment,
+            finalized_only: self.finalized_only,
+            block_fetch_concurrency: self.block_fetch_concurrency,
+            vote_accounts_ttl: self.vote_accounts_ttl,
+            supermajority_stake: self.supermajority_stake,
+            supermajority_threshold: self.supermajority_threshold,
+            vote_window: self.vote_window,
+            vote_program_id: self.vote_program_id,
+            bank_hash_scheme: self.bank_hash_scheme,
+            epoch_accounts_hashes: self.epoch_accounts_hashes,
+            version_check: self.version_check,
+            cluster_version: self.cluster_version,
+            retry_policy: self.retry_policy,
+            block_header_cache: self.block_header_cache,
+            follow_state_file: self.follow_state_file,
+            block_encoding: self.block_encoding,
+            #[cfg(feature = "geyser")]
+            geyser: self.geyser,
+            vote_accounts_cache: self.vote_accounts_cache,
+            epoch_schedule: self.epoch_schedule,
+        }
+    }
+
+    /// websocket endpoint used for subscriptions, derived from the http 
+    /// endpoint when not set
+    pub fn with_ws_endpoint(mut self, ws_endpoint: impl Into<String>) -> Self { 
+        self.ws_endpoint = Some(ws_endpoint.into());
+        self
+    }
+
+    /// commitment used for `getBlock`, `getTransaction` and the rpc client 
+    /// (eg. `get_latest_blockhash`), defaults to `confirmed`. 
+    /// 
+    /// `getBlock` and `getTransaction` dont support `processed`, so those use 
+    /// `confirmed` instead. blocks at `confirmed` may not have all their votes 
+    /// landed yet, so scan more slots ahead to reach a supermajority
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self { 
+        self.commitment = commitment;
+        self.rpc_client = Some(RpcClient::new_with_timeout_and_commitment(self.endpoint.clone(), self.timeout, commitment));
+        self
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig { 
+        self.commitment
+    }
+
+    /// fetch the transaction, its block and the blocks scanned for votes at 
+    /// `finalized` whatever the commitment, so a slot which is confirmed but 
+    /// later rolled back is never verified. transactions arent available 
+    /// until they are finalized, and votes only count from blocks which are 
+    /// finalized already (later slots in the vote window show as skipped)
+    pub fn with_finalized_only(mut self, finalized_only: bool) -> Self { 
+        self.finalized_only = finalized_only;
+        self
+    }
+
+    pub fn finalized_only(&self) -> bool { 
+        self.finalized_only
+    }
+
+    // getBlock and getTransaction reject commitments below confirmed
+    pub(crate) fn block_commitment(&self) -> CommitmentLevel { 
+        if self.finalized_only { 
+            return CommitmentLevel::Finalized;
+        }
+        match self.commitment.commitment { 
+            CommitmentLevel::Finalized => CommitmentLevel::Finalized,
+            _ => CommitmentLevel::Confirmed,
+        }
+    }
+
+    /// the commitment `slot` has reached: `finalized` once the cluster has 
+    /// finalized it, otherwise `confirmed` (blocks arent fetched at any 
+    /// lower commitment)
+    pub async fn slot_commitment(&self, slot: u64) -> Result<CommitmentLevel, LightNodeError> { 
+        // then only finalized blocks are fetched
+        if self.block_commitment() == CommitmentLevel::Finalized { 
+            return Ok(CommitmentLevel::Finalized);
+        }
+        let finalized_slot = self.transport.get_epoch_info(CommitmentConfig::finalized()).await?.absolute_slot;
+        if slot <= finalized_slot { 
+            Ok(CommitmentLevel::Finalized)
+        } else { 
+            Ok(CommitmentLevel::Confirmed)
+        }
+    }
+
+    /// max number of blocks requested at once when scanning for votes
+    pub fn with_block_fetch_concurrency(mut self, block_fetch_concurrency: usize) -> Self { 
+        self.block_fetch_concurrency = block_fetch_concurrency.max(1);
+        self
+    }
+
+    pub fn block_fetch_concurrency(&self) -> usize { 
+        self.block_fetch_concurrency
+    }
+
+    /// how long cached vote account stakes are reused within the same epoch
+    pub fn with_vote_accounts_ttl(mut self, vote_accounts_ttl: Duration) -> Self { 
+        self.vote_accounts_ttl = vote_accounts_ttl;
+        self
+    }
+
+    /// stake the supermajority threshold is applied against, defaults to 
+    /// the total stake (delinquent stake included)
+    pub fn with_supermajority_stake(mut self, supermajority_stake: SupermajorityStake) -> Self { 
+        self.supermajority_stake = supermajority_stake;
+        self
+    }
+
+    pub fn supermajority_stake(&self) -> SupermajorityStake { 
+        self.supermajority_stake
+    }
+
+    /// share of the threshold stake a bank hash needs, defaults to 2/3. eg. 
+    /// a stricter 3/4 for a safety margin over the cluster's own threshold
+    pub fn with_supermajority_threshold(mut self, supermajority_threshold: SupermajorityThreshold) -> Self { 
+        self.supermajority_threshold = supermajority_threshold;
+        self
+    }
+
+    pub fn supermajority_threshold(&self) -> SupermajorityThreshold { 
+        self.supermajority_threshold
+    }
+
+    /// how many slots after a slot are scanned for votes on it, defaults 
+    /// to a fixed 5 slots
+    pub fn with_vote_window(mut self, vote_window: VoteWindow) -> Self { 
+        self.vote_window = vote_window;
+        self
+    }
+
+    pub fn vote_window(&self) -> VoteWindow { 
+        self.vote_window
+    }
+
+    /// program whose instructions are scanned for votes, defaults to the 
+    /// vote program. for clusters with the vote program at another address
+    pub fn with_vote_program_id(mut self, vote_program_id: Pubkey) -> Self { 
+        self.vote_program_id = vote_program_id;
+        self
+    }
+
+    pub fn vote_program_id(&self) -> &Pubkey { 
+        &self.vote_program_id
+    }
+
+    /// how bank hashes are recomputed, defaults to the 1.16 formula (with the 
+    /// epoch accounts hash). see `detect_bank_hash_scheme`
+    pub fn with_bank_hash_scheme(mut self, bank_hash_scheme: impl BankHashScheme + 'static) -> Self { 
+        self.bank_hash_scheme = Arc::new(bank_hash_scheme);
+        self
+    }
+
+    pub fn bank_hash_scheme(&self) -> &dyn BankHashScheme { 
+        self.bank_hash_scheme.as_ref()
+    }
+
+    /// the epoch accounts hash the bank of `slot` mixes in (the slot 3/4 
+    /// into an epoch), which `getBlockHeaders` doesnt return. obtained 
+    /// elsewhere, eg. from a validator's snapshot. without it that slot's 
+    /// recomputed bank hash doesnt match
+    pub fn with_epoch_accounts_hash(mut self, slot: u64, epoch_accounts_hash: Hash) -> Self { 
+        self.epoch_accounts_hashes.insert(slot, epoch_accounts_hash);
+        self
+    }
+
+    /// the epoch accounts hash set for `slot` with `with_epoch_accounts_hash`
+    pub fn epoch_accounts_hash(&self, slot: u64) -> Option<Hash> { 
+        self.epoch_accounts_hashes.get(&slot).copied()
+    }
+
+    /// pick the bank hash scheme from the cluster version (`getVersion`), 
+    /// keeping the current one if the version isnt recognized
+    pub async fn detect_bank_hash_scheme(mut self) -> Result<Self, LightNodeError> { 
+        let version = self.cluster_version().await?.to_string();
+        match bank_hash_scheme_for_version(&version) { 
+            Some(bank_hash_scheme) => { 
+                debug!(%version, ?bank_hash_scheme, "detected bank hash scheme");
+                self.bank_hash_scheme = Arc::from(bank_hash_scheme);
+            }
+            None => warn!(%version, "unknown cluster version, keeping the bank hash scheme"),
+        }
+        Ok(self)
+    }
+
+    /// whether verification warns (the default) or fails when the cluster 
+    /// runs a version outside `MIN_SUPPORTED_VERSION..MAX_SUPPORTED_VERSION`
+    pub fn with_version_check(mut self, version_check: VersionCheck) -> Self { 
+        self.version_check = version_check;
+        self
+    }
+
+    pub fn version_check(&self) -> VersionCheck { 
+        self.version_check
+    }
+
+    /// the cluster's `solana-core` version from `getVersion`, fetched once
+    pub async fn cluster_version(&self) -> Result<&str, LightNodeError> { 
+        self.cluster_version
+            .get_or_try_init(|| async { Ok::<_, LightNodeError>(self.transport.get_version().await?.solana_core) })
+            .await
+            .map(String::as_str)
+    }
+
+    /// check the cluster version is supported as configured by 
+    /// `with_version_check`, returning it (`None` when the check is off)
+    pub async fn check_cluster_version(&self) -> Result<Option<String>, LightNodeError> { 
+        if self.version_check == VersionCheck::Off { 
+            return Ok(None);
+        }
+        let version = match self.cluster_version().await { 
+            Ok(version) => version.to_string(),
+            Err(e) if self.version_check == VersionCheck::Warn => { 
+                warn!("couldnt check the cluster version: {}", e);
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let supported = version.parse::<ClusterVersion>().map(|x| x.is_supported()).unwrap_or(false);
+        if !supported { 
+            if self.version_check == VersionCheck::Error { 
+                return Err(LightNodeError::UnsupportedVersion { 
+                    version,
+                    min: MIN_SUPPORTED_VERSION,
+                    max: MAX_SUPPORTED_VERSION,
+                });
+            }
+            warn!(%version, min = %MIN_SUPPORTED_VERSION, max = %MAX_SUPPORTED_VERSION, "cluster version is outside the supported range, verification may fail");
+        }
+        Ok(Some(version))
+    }
+
+    /// how `getBlock`, `getTransaction` and `getBlockHeaders` are retried
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self { 
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy { 
+        &self.retry_policy
+    }
+
+    /// cache block headers in `cache_dir`, so verifying the same slot and 
+    /// signature again doesnt refetch them
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self { 
+        self.block_header_cache = Some(BlockHeaderCache::new(cache_dir));
+        self
+    }
+
+    /// refetch cached block headers older than `ttl`, needs `with_cache_dir` first
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self { 
+        self.block_header_cache = self.block_header_cache.map(|cache| cache.with_ttl(ttl));
+        self
+    }
+
+    pub fn block_header_cache(&self) -> Option<&BlockHeaderCache> { 
+        self.block_header_cache.as_ref()
+    }
+
+    /// record the last slot `follow` verified in `state_file` (json), and 
+    /// resume from the slot after it when following again
+    pub fn with_follow_state_file(mut self, state_file: impl Into<PathBuf>) -> Self { 
+        self.follow_state_file = Some(state_file.into());
+        self
+    }
+
+    pub fn follow_state_file(&self) -> Option<&Path> { 
+        self.follow_state_file.as_deref()
+    }
+
+    /// encoding of the transactions in fetched blocks, defaults to base58
+    pub fn with_block_encoding(mut self, block_encoding: BlockEncoding) -> Self { 
+        self.block_encoding = block_encoding;
+        self
+    }
+
+    pub fn block_encoding(&self) -> BlockEncoding { 
+        self.block_encoding
+    }
+
+    /// stream the blocks scanned for votes from a yellowstone geyser grpc 
+    /// endpoint instead of polling `getBlock`, see `parse_geyser_votes`. 
+    /// block headers are still fetched over rpc
+    #[cfg(feature = "geyser")]
+    pub fn with_geyser(mut self, geyser: GeyserConfig) -> Self { 
+        self.geyser = Some(geyser);
+        self
+    }
+
+    #[cfg(feature = "geyser")]
+    pub fn geyser(&self) -> Option<&GeyserConfig> { 
+        self.geyser.as_ref()
+    }
+
+    pub fn endpoint(&self) -> &str { 
+        &self.endpoint
+    }
+
+    /// the transport requests are sent with
+    pub fn transport(&self) -> &T { 
+        &self.transport
+    }
+
+    /// the configured websocket endpoint, or the http endpoint with a ws 
+    /// scheme and the port after the rpc port (as solana validators serve it). 
+    /// fails with `NoWsEndpoint` when it cant be derived, eg. for port 65535
+    pub fn ws_endpoint(&self) -> Result<String, LightNodeError> { 
+        match &self.ws_endpoint { 
+            Some(ws_endpoint) => Ok(ws_endpoint.clone()),
+            None => derive_ws_endpoint(&self.endpoint)
+                .ok_or_else(|| LightNodeError::NoWsEndpoint { endpoint: self.endpoint.clone() }),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration { 
+        self.timeout
+    }
+
+    pub fn connect_timeout(&self) -> Duration { 
+        self.connect_timeout
+    }
+
+    pub fn max_connections(&self) -> usize { 
+        self.max_connections
+    }
+
+    pub fn rpc_client(&self) -> &RpcClient { 
+        self.rpc_client.as_ref().expect("light client has no rpc client")
+    }
+
+    /// poll the balance of `pubkey` until it has at least `min_lamports`, 
+    /// eg. for an airdrop to land. fails with `BalanceTimeout` if it doesnt 
+    /// within `timeout`
+    pub async fn wait_for_balance(&self, pubkey: &Pubkey, min_lamports: u64, timeout: Duration) -> Result<u64, LightNodeError> { 
+        let wait = async { 
+            loop { 
+                let balance = self.rpc_client().get_balance(pubkey).await?;
+                if balance >= min_lamports { 
+                    return Ok::<_, LightNodeError>(balance);
+                }
+                tokio::time::sleep(BALANCE_POLL_INTERVAL).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| LightNodeError::BalanceTimeout { pubkey: *pubkey, min_lamports, timeout })?
+    }
+
+    /// fetch a block, retrying while it isnt available yet. a skipped slot 
+    /// fails with `SlotSkipped`, and a null result (skipped or pruned) with 
+    /// `BlockMissing`, straight away
+    pub async fn get_block(&self, slot: u64) -> Result<GetBlockResponse, LightNodeError> { 
+        let config = serde_json::json!({ 
+            "encoding": self.block_encoding.as_str(),
+            "maxSupportedTransactionVersion": 0,
+            "commitment": self.block_commitment().to_string(),
+        });
+        self.get_block_with_config(slot, config).await
+    }
+
+    /// the signatures of the transactions in a block, without the transactions
+    pub async fn get_block_signatures(&self, slot: u64) -> Result<Vec<Signature>, LightNodeError> { 
+        let config = serde_json::json!({ 
+            "transactionDetails": "signatures",
+            "rewards": false,
+            "maxSupportedTransactionVersion": 0,
+            "commitment": self.block_commitment().to_string(),
+        });
+        let block = self.get_block_with_config(slot, config).await?.result;
+        let signatures = block.signatures
+            .unwrap_or_default()
+            .iter()
+            .map(|signature| Signature::from_str(signature))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(signatures)
+    }
+
+    /// the blockhashes and signature count `getBlock` reports for the slot
+    pub async fn get_block_summary(&self, slot: u64) -> Result<BlockSummary, LightNodeError> { 
+        let block = self.get_block(slot).await?.result;
+        summarize_block(&block)
+    }
+
+    /// the blockhash `getBlock` reports for the slot
+    pub async fn get_blockhash(&self, slot: u64) -> Result<Hash, LightNodeError> { 
+        let config = serde_json::json!({ 
+            "transactionDetails": "none",
+            "rewards": false,
+            "maxSupportedTransactionVersion": 0,
+            "commitment": self.block_commitment().to_string(),
+        });
+        let block = self.get_block_with_config(slot, config).await?.result;
+        Ok(Hash::from_str(&block.blockhash)?)
+    }
+
+    async fn get_block_with_config(&self, slot: u64, config: serde_json::Value) -> Result<GetBlockResponse, LightNodeError> { 
+        retry(&self.retry_policy, || self.get_block_once(slot, config.clone()))
+            .await
+            .map_err(|e| if e.is_transient() { 
+                LightNodeError::BlockUnavailable { slot, attempts: self.retry_policy.max_attempts }
+            } else { 
+                e
+            })
+    }
+
+    async fn get_block_once(&self, slot: u64, config: serde_json::Value) -> Result<GetBlockResponse, LightNodeError> { 
+        let resp = self.retry_policy.attempt("getBlock", self.transport.get_block(slot, config)).await?;
+        // `result` is null when the slot was skipped or its block pruned
+        match serde_json::from_str::<JsonRpcResponse<Option<UiConfirmedBlock>>>(&resp)? { 
+            JsonRpcResponse::Result { jsonrpc, result: Some(result), id } => Ok(GetBlockResponse { jsonrpc, result, id }),
+            JsonRpcResponse::Result { result: None, .. } => Err(LightNodeError::BlockMissing { slot }),
+            // distinguish a skipped slot from the block not being available yet
+            JsonRpcResponse::Error { error, .. } if SLOT_SKIPPED_ERROR_CODES.contains(&error.code) => { 
+                Err(LightNodeError::SlotSkipped { slot, code: error.code, message: error.message })
+            }
+            JsonRpcResponse::Error { error, .. } => Err(error.into()),
+        }
+    }
+
+    pub async fn epoch_schedule(&self) -> Result<&EpochSchedule, LightNodeError> { 
+        self.epoch_schedule.get_or_try_init(|| self.transport.get_epoch_schedule()).await
+    }
+
+    /// vote account stakes, cached until the epoch changes or the ttl expires
+    pub async fn vote_account_stakes(&self) -> Result<Arc<VoteAccountStakes>, LightNodeError> { 
+        let epoch = self.transport.get_epoch_info(self.commitment).await?.epoch;
+        if let Some(cached) = self.vote_accounts_cache.lock().unwrap().as_ref() { 
+            if cached.stakes.epoch == epoch && cached.fetched_at.elapsed() < self.vote_accounts_ttl { 
+                return Ok(cached.stakes.clone());
+            }
+        }
+        self.fetch_vote_account_stakes(epoch).await
+    }
+
+    /// drop any cached vote account stakes and fetch them again
+    pub async fn refresh_vote_accounts(&self) -> Result<Arc<VoteAccountStakes>, LightNodeError> { 
+        self.vote_accounts_cache.lock().unwrap().take();
+        let epoch = self.transport.get_epoch_info(self.commitment).await?.epoch;
+        self.fetch_vote_account_stakes(epoch).await
+    }
+
+    async fn fetch_vote_account_stakes(&self, epoch: u64) -> Result<Arc<VoteAccountStakes>, LightNodeError> { 
+        let vote_accounts = self.transport.get_vote_accounts(self.commitment).await?;
+        // votes are signed by the authorized voter, which can differ from the 
+        // node identity, so stake is keyed by the vote account instead
+        let stakes = vote_accounts.current
+            .iter()
+            .chain(vote_accounts.delinquent.iter())
+            .map(|x| Ok((Pubkey::from_str(&x.vote_pubkey)?, x.activated_stake)))
+            .collect::<Result<HashMap<_, _>, LightNodeError>>()?;
+        let total_stake = stakes.values().map(|stake| u128::from(*stake)).sum::<u128>();
+        let current_total_stake = vote_accounts.current.iter().map(|x| u128::from(x.activated_stake)).sum::<u128>();
+        let delinquent_stake = vote_accounts.delinquent.iter().map(|x| u128::from(x.activated_stake)).sum::<u128>();
+
+        let stakes = Arc::new(VoteAccountStakes { epoch, total_stake, current_total_stake, delinquent_stake, stakes });
+        *self.vote_accounts_cache.lock().unwrap() = Some(CachedVoteAccountStakes { 
+            stakes: stakes.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(stakes)
+    }
+
+    pub async fn parse_block_votes(&self, target_slot: u64, slots_ahead: u64) -> Result<VoteTally, LightNodeError> {
+        self.parse_block_votes_with_stakes(target_slot, slots_ahead, None).await
+    }
+
+    /// `parse_block_votes` weighing the votes with `stakes` (activated stake 
+    /// keyed by vote account pubkey, not node identity) when given, eg. from 
+    /// a validator or indexer which already tracks them, instead of fetching 
+    /// them with `getVoteAccounts`. the stakes are taken to be from the 
+    /// slot's epoch
+    pub async fn parse_block_votes_with_stakes(&self, target_slot: u64, slots_ahead: u64, stakes: Option<&HashMap<Pubkey, u64>>) -> Result<VoteTally, LightNodeError> { 
+        let end_slot = target_slot + slots_ahead.max(1) - 1;
+        let (vote_account_stakes, slot_epoch) = match stakes { 
+            Some(stakes) => { 
+                let slot_epoch = self.epoch_schedule().await?.get_epoch(target_slot);
+                (Arc::new(VoteAccountStakes::from_stakes(slot_epoch, stakes.clone())), slot_epoch)
+            }
+            None => { 
+                let vote_account_stakes = self.vote_account_stakes().await?;
+                let slot_epoch = self.slot_epoch(target_slot, &vote_account_stakes).await?;
+                (vote_account_stakes, slot_epoch)
+            }
+        };
+
+        let scanned_votes = self.scan_votes(target_slot, target_slot, end_slot).await?;
+        let mut vote_counter = VoteCounter::default();
+        for (_, candidate) in scanned_votes.verified.iter() { 
+            vote_counter.add(candidate, &vote_account_stakes.stakes);
+        }
+
+        Ok(VoteTally { 
+            slot: target_slot,
+            slot_epoch,
+            stake_epoch: vote_account_stakes.epoch,
+            total_stake: vote_account_stakes.total_stake,
+            current_total_stake: vote_account_stakes.current_total_stake,
+            delinquent_stake: vote_account_stakes.delinquent_stake,
+            per_bank_hash: vote_counter.per_bank_hash,
+            unattributed_votes: vote_counter.unattributed_votes,
+            signature_verification_failures: scanned_votes
+                .signature_verification_failures
+                .into_iter()
+                .map(|(_, vote_account)| vote_account)
+                .collect(),
+            skipped_slots: scanned_votes.skipped_slots,
+            unavailable_slots: scanned_votes.unavailable_slots,
+            slots_scanned: end_slot - target_slot + 1,
+        })
+    }
+
+    /// like `parse_block_votes`, but keeps scanning the slots after 
+    /// `target_slot` until the votes for `bank_hash` reach the client's 
+    /// supermajority threshold or `max_slots_ahead` slots were scanned. 
+    /// `slots_scanned` of the tally is how far the scan got
+    pub async fn parse_block_votes_until(&self, target_slot: u64, bank_hash: Hash, max_slots_ahead: u64) -> Result<VoteTally, LightNodeError> {
+        self.parse_block_votes_with_progress(target_slot, bank_hash, max_slots_ahead, |_| {}).await
+    }
+
+    /// `parse_block_votes_until`, calling `on_progress` with the running 
+    /// stake for `bank_hash` after each scanned slot, eg. to show progress 
+    /// towards the supermajority
+    pub async fn parse_block_votes_with_progress<F>(&self, target_slot: u64, bank_hash: Hash, max_slots_ahead: u64, mut on_progress: F) -> Result<VoteTally, LightNodeError> 
+    where 
+        F: FnMut(VoteProgress),
+    {
+        let max_end_slot = target_slot + max_slots_ahead.max(1) - 1;
+        let vote_account_stakes = self.vote_account_stakes().await?;
+        let slot_epoch = self.slot_epoch(target_slot, &vote_account_stakes).await?;
+
+        let mut tally = VoteTally { 
+            slot: target_slot,
+            slot_epoch,
+            stake_epoch: vote_account_stakes.epoch,
+            total_stake: vote_account_stakes.total_stake,
+            current_total_stake: vote_account_stakes.current_total_stake,
+            delinquent_stake: vote_account_stakes.delinquent_stake,
+            per_bank_hash: HashMap::new(),
+            unattributed_votes: 0,
+            signature_verification_failures: vec![],
+            skipped_slots: vec![],
+            unavailable_slots: vec![],
+            slots_scanned: 0,
+        };
+        let threshold_stake = tally.threshold_stake(self.supermajority_stake);
+
+        // fetch as many slots at a time as are fetched concurrently, then 
+        // count them one slot at a time
+        let step = self.block_fetch_concurrency.max(1) as u64;
+        let mut vote_counter = VoteCounter::default();
+        let mut start_slot = target_slot;
+        'scan: while start_slot <= max_end_slot { 
+            let end_slot = (start_slot + step - 1).min(max_end_slot);
+            let scanned_votes = self.scan_votes(target_slot, start_slot, end_slot).await?;
+            let mut vote_candidates = scanned_votes.verified.into_iter().peekable();
+            for slot in start_slot..=end_slot { 
+                while let Some((_, candidate)) = vote_candidates.next_if(|(candidate_slot, _)| *candidate_slot == slot) { 
+                    vote_counter.add(&candidate, &vote_account_stakes.stakes);
+                }
+                tally.signature_verification_failures.extend(scanned_votes
+                    .signature_verification_failures
+                    .iter()
+                    .filter(|(failure_slot, _)| *failure_slot == slot)
+                    .map(|(_, vote_account)| *vote_account));
+                if scanned_votes.skipped_slots.contains(&slot) { 
+                    tally.skipped_slots.push(slot);
+                }
+                if scanned_votes.unavailable_slots.contains(&slot) { 
+                    tally.unavailable_slots.push(slot);
+                }
+                tally.slots_scanned = slot - target_slot + 1;
+
+                let progress = VoteProgress { 
+                    slot,
+                    slots_scanned: tally.slots_scanned,
+                    voted_stake: bank_hash_stake(&vote_counter.per_bank_hash, &bank_hash),
+                    threshold_stake,
+                    total_stake: tally.total_stake,
+                    supermajority_threshold: self.supermajority_threshold,
+                };
+                debug!(?progress, "scanned votes");
+                on_progress(progress);
+                if progress.is_supermajority() { 
+                    break 'scan;
+                }
+            }
+            start_slot = end_slot + 1;
+        }
+
+        tally.per_bank_hash = vote_counter.per_bank_hash;
+        tally.unattributed_votes = vote_counter.unattributed_votes;
+        Ok(tally)
+    }
+
+    // rpc only serves the current stake, so older slots are checked 
+    // against a newer stake distribution
+    pub(crate) async fn slot_epoch(&self, target_slot: u64, vote_account_stakes: &VoteAccountStakes) -> Result<u64, LightNodeError> { 
+        let slot_epoch = self.epoch_schedule().await?.get_epoch(target_slot);
+        let stake_epoch = vote_account_stakes.epoch;
+        if slot_epoch != stake_epoch { 
+            warn!(target_slot, slot_epoch, stake_epoch, "slot is in a past epoch, using current stake as an approximation");
+        }
+        Ok(slot_epoch)
+    }
+
+    /// the slots with a block between `start_slot` and `end_slot` (inclusive), 
+    /// at `getBlock`'s commitment. wide ranges take several `getBlocks` requests
+    pub async fn get_confirmed_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>, LightNodeError> { 
+        let commitment = CommitmentConfig { commitment: self.block_commitment() };
+        let mut slots = vec![];
+        let mut chunk_start = start_slot;
+        while chunk_start <= end_slot { 
+            let chunk_end = end_slot.min(chunk_start.saturating_add(MAX_GET_BLOCKS_RANGE - 1));
+            slots.extend(self.transport.get_blocks(chunk_start, chunk_end, commitment).await?);
+            if chunk_end == u64::MAX { 
+                break;
+            }
+            chunk_start = chunk_end + 1;
+        }
+        Ok(slots)
+    }
+
+    // the votes on `target_slot` in the blocks of `start_slot..=end_slot`, 
+    // and the slots in the range without a block
+    pub(crate) async fn scan_votes(&self, target_slot: u64, start_slot: u64, end_slot: u64) -> Result<ScannedVotes, LightNodeError> {
+        // signatures of the votes are verified in one batch after the scan
+        let mut vote_candidates = vec![];
+
+        // only fetch the slots which have a block. slots missing before the 
+        // last one returned were skipped, but later ones may not be confirmed 
+        // yet so those are still requested
+        let confirmed_slots = self.get_confirmed_blocks(start_slot, end_slot).await?;
+        let last_confirmed_slot = confirmed_slots.last().copied();
+        let is_pending = |slot: u64| match last_confirmed_slot { 
+            Some(last_confirmed_slot) => slot > last_confirmed_slot,
+            None => true,
+        };
+        let mut skipped_slots = vec![];
+        let mut slots = vec![];
+        for slot in start_slot..=end_slot { 
+            if is_pending(slot) || confirmed_slots.contains(&slot) { 
+                slots.push(slot);
+            } else { 
+                skipped_slots.push(slot);
+            }
+        }
+        if !skipped_slots.is_empty() { 
+            debug!(?skipped_slots, "skipped slots in the vote window");
+        }
+
+        // fetch the blocks concurrently, votes are deduped so the order they 
+        // are processed in doesnt matter
+        let blocks = stream::iter(slots)
+            .map(|slot| async move { 
+                debug!(slot, "requesting block");
+                (slot, self.get_block(slot).await)
+            })
+            .buffer_unordered(self.block_fetch_concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut unavailable_slots = vec![];
+        for (slot, resp) in blocks {
+            let resp = match resp { 
+                Ok(resp) => resp,
+                Err(LightNodeError::SlotSkipped { .. } | LightNodeError::BlockMissing { .. }) => { 
+                    debug!(slot, "slot was skipped");
+                    skipped_slots.push(slot);
+                    continue;
+                }
+                // the slot may not be confirmed yet, so count it rather than 
+                // failing the slots which did arrive
+                Err(LightNodeError::BlockUnavailable { attempts, .. }) if is_pending(slot) => { 
+                    warn!(slot, attempts, "pending slot still unavailable, skipping its votes");
+                    unavailable_slots.push(slot);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let block = resp.result;
+        
+            for (tx, meta) in decode_block_transactions_with_meta(&block)? { 
+                let account_keys = resolve_account_keys(&tx.message, meta)?;
+                if let Some(candidate) = vote_candidate(tx, &account_keys, target_slot, &self.vote_program_id) { 
+                    vote_candidates.push((slot, candidate));
+                }
+            }
+        }
+
+        let verified = verify_vote_candidates(vote_candidates.iter().map(|(_, candidate)| candidate));
+        let mut scanned_votes = ScannedVotes { 
+            verified: vec![],
+            signature_verification_failures: vec![],
+            skipped_slots,
+            unavailable_slots,
+        };
+        for ((slot, candidate), verified) in vote_candidates.into_iter().zip(verified) { 
+            if verified { 
+                scanned_votes.verified.push((slot, candidate));
+            } else { 
+                // a corrupted block, or a bug decoding it
+                warn!(slot, vote_account = %candidate.vote_account, "vote signature verification failed");
+                scanned_votes.signature_verification_failures.push((slot, candidate.vote_account));
+            }
+        }
+        // blocks are fetched concurrently so arrive out of order
+        scanned_votes.verified.sort_by_key(|(slot, _)| *slot);
+        scanned_votes.signature_verification_failures.sort_by_key(|(slot, _)| *slot);
+        Ok(scanned_votes)
+    }
+
+
+    pub async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<GetBlockHeadersResponse, LightNodeError> { 
+        if let Some(cache) = &self.block_header_cache { 
+            if let Some(result) = cache.get(slot, &signature).await { 
+                // served from the cache, so no request was sent
+                return Ok(GetBlockHeadersResponse { jsonrpc: "2.0".to_string(), result, id: RpcId::Num(0) });
+            }
+        }
+
+        let resp = retry(&self.retry_policy, || self.get_block_headers_once(slot, signature)).await?;
+        if let Some(cache) = &self.block_header_cache { 
+            cache.put(slot, &signature, &resp.result).await;
+        }
+        Ok(resp)
+    }
+
+    async fn get_block_headers_once(&self, slot: u64, signature: Signature) -> Result<GetBlockHeadersResponse, LightNodeError> { 
+        let resp = self.retry_policy.attempt("getBlockHeaders", self.transport.get_block_headers(slot, signature)).await?;
+        match serde_json::from_str::<JsonRpcResponse<Vec<u8>>>(&resp) { 
+            Ok(JsonRpcResponse::Result { jsonrpc, result, id }) => Ok(GetBlockHeadersResponse { jsonrpc, result, id }),
+            Ok(JsonRpcResponse::Error { error, .. }) if error.code == METHOD_NOT_FOUND_ERROR_CODE => { 
+                Err(LightNodeError::BlockHeadersUnsupported { endpoint: self.endpoint.clone() })
+            }
+            Ok(JsonRpcResponse::Error { error, .. }) => Err(error.into()),
+            // not an rpc error either, keep the body around to debug it
+            Err(e) => Err(LightNodeError::MalformedResponse { 
+                method: "getBlockHeaders".to_string(),
+                source: e,
+                body: truncate_body(&resp),
+            }),
+        }
+    }
+
+    /// fetch a transaction, retrying while it isnt available yet (see 
+    /// `with_retry_policy` for the attempts and polling interval). it is 
+    /// json encoded, so only its slot and status are of use, see 
+    /// `get_encoded_tx` for the transaction itself. 
+    /// 
+    /// fails with `TransactionDropped` when the cluster has no status for the 
+    /// transaction after the last attempt (it never landed), or with 
+    /// `TransactionUnavailable` when it is still processing
+    pub async fn get_tx(&self, signature: Signature) -> Result<GetTransactionResponse, LightNodeError> { 
+        self.get_tx_with_encoding(signature, "json").await
+    }
+
+    /// `get_tx` with the transaction binary encoded, so it can be decoded 
+    /// with `GetTransactionResponse::transaction`
+    pub async fn get_encoded_tx(&self, signature: Signature, encoding: BlockEncoding) -> Result<GetTransactionResponse, LightNodeError> { 
+        self.get_tx_with_encoding(signature, encoding.as_str()).await
+    }
+
+    async fn get_tx_with_encoding(&self, signature: Signature, encoding: &str) -> Result<GetTransactionResponse, LightNodeError> { 
+        let attempts = self.retry_policy.max_attempts;
+        let e = match retry(&self.retry_policy, || self.get_tx_once(signature, encoding)).await { 
+            Err(e) if e.is_transient() => e,
+            result => return result,
+        };
+        debug!(%signature, "tx unavailable: {}", e);
+
+        // tell a tx which is still processing from one which never landed
+        match self.get_signature_status(signature).await { 
+            Ok(None) => Err(LightNodeError::TransactionDropped { signature, attempts }),
+            Ok(Some(_)) => Err(LightNodeError::TransactionUnavailable { signature, attempts }),
+            Err(e) => { 
+                warn!(%signature, "failed to get the tx status: {}", e);
+                Err(LightNodeError::TransactionUnavailable { signature, attempts })
+            }
+        }
+    }
+
+    /// the cluster's status of a transaction, searching the ledger history 
+    /// too. `None` when the cluster has never seen it
+    pub async fn get_signature_status(&self, signature: Signature) -> Result<Option<TransactionStatus>, LightNodeError> { 
+        let statuses = self.transport.get_signature_statuses(&[signature]).await?;
+        Ok(statuses.into_iter().next().flatten())
+    }
+
+    async fn get_tx_once(&self, signature: Signature, encoding: &str) -> Result<GetTransactionResponse, LightNodeError> { 
+        let config = serde_json::json!({
+            "commitment": self.block_commitment().to_string(),
+            "encoding": encoding,
+            "maxSupportedTransactionVersion": 0,
+        });
+        let resp = self.transport.get_transaction(signature, config).await?;
+        // the result is null until the tx is available
+        match serde_json::from_str::<JsonRpcResponse<Option<EncodedConfirmedTransactionWithStatusMeta>>>(&resp)? { 
+            JsonRpcResponse::Result { jsonrpc, result: Some(result), id } => Ok(GetTransactionResponse { jsonrpc, result, id }),
+            JsonRpcResponse::Result { result: None, .. } => Err(LightNodeError::NullResult { method: "getTransaction".to_string() }),
+            JsonRpcResponse::Error { error, .. } => Err(error.into()),
+        }
+    }
+}
+
+// how much of a response body is kept in a `MalformedResponse`
+const MAX_ERROR_BODY_LEN: usize = 512;
+
+fn truncate_body(body: &str) -> String { 
+    match body.char_indices().nth(MAX_ERROR_BODY_LEN) { 
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+fn derive_ws_endpoint(endpoint: &str) -> Option<String> { 
+    let mut url = reqwest::Url::parse(endpoint).ok()?;
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    // http(s) -> ws(s) is always allowed
+    let _ = url.set_scheme(scheme);
+    if let Some(port) = url.port() { 
+        url.set_port(Some(port.checked_add(1)?)).ok()?;
+    }
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests { 
+    use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+    use serde_json::json;
+    use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction, vote::{instruction::vote, state::Vote}};
+
+    use crate::transport::MemoryTransport;
+
+    use super::*;
+
+    // a light client over `transport` which retries 3 times without waiting
+    pub(crate) fn test_client(transport: MemoryTransport) -> LightClient<MemoryTransport> { 
+        let retry_policy = RetryPolicy { 
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        };
+        LightClient::new("http://127.0.0.1:8899")
+            .with_transport(transport)
+            .with_retry_policy(retry_policy)
+    }
+
+    // a vote by `vote_account` (also its authorized voter) for `bank_hash` 
+    // on `slot`
+    pub(crate) fn vote_tx(vote_account: &Keypair, slot: u64, bank_hash: Hash) -> VersionedTransaction { 
+        let ix = vote(&vote_account.pubkey(), &vote_account.pubkey(), Vote::new(vec![slot], bank_hash));
+        Transaction::new_signed_with_payer(&[ix], Some(&vote_account.pubkey()), &[vote_account], Hash::new_unique()).into()
+    }
+
+    // a `getBlock` result with base58 encoded `transactions`
+    pub(crate) fn block_json(slot: u64, transactions: &[VersionedTransaction]) -> serde_json::Value { 
+        let transactions: Vec<_> = transactions.iter()
+            .map(|tx| json!({ 
+                "transaction": [bs58::encode(bincode::serialize(tx).unwrap()).into_string(), "base58"],
+                "meta": null,
+            }))
+            .collect();
+        json!({ 
+            "previousBlockhash": Hash::new_unique().to_string(),
+            "blockhash": Hash::new_unique().to_string(),
+            "parentSlot": slot.saturating_sub(1),
+            "transactions": transactions,
+            "blockTime": null,
+            "blockHeight": slot,
+        })
+    }
+
+    // epoch 0 info, the epoch schedule and the vote accounts with `stakes`
+    pub(crate) fn with_stakes(transport: MemoryTransport, stakes: &[(Pubkey, u64)]) -> MemoryTransport { 
+        let current: Vec<_> = stakes.iter()
+            .map(|(vote_account, stake)| json!({ 
+                "votePubkey": vote_account.to_string(),
+                "nodePubkey": Pubkey::new_unique().to_string(),
+                "activatedStake": stake,
+                "commission": 0,
+                "epochVoteAccount": true,
+                "epochCredits": [],
+                "lastVote": 0,
+                "rootSlot": 0,
+            }))
+            .collect();
+        transport
+            .with_result("getEpochInfo", "", json!({ 
+                "epoch": 0,
+                "slotIndex": 100,
+                "slotsInEpoch": 432_000,
+                "absoluteSlot": 100,
+                "blockHeight": 100,
+                "transactionCount": null,
+            }))
+            .with_result("getEpochSchedule", "", EpochSchedule::without_warmup())
+            .with_result("getVoteAccounts", "", json!({ "current": current, "delinquent": [] }))
+    }
+
+    #[test]
+    fn test_derive_ws_endpoint() { 
+        assert_eq!(derive_ws_endpoint("http://127.0.0.1:8899").as_deref(), Some("ws://127.0.0.1:8900/"));
+        assert_eq!(derive_ws_endpoint("https://rpc.example.com").as_deref(), Some("wss://rpc.example.com/"));
+        assert_eq!(derive_ws_endpoint("http://127.0.0.1:65535"), None);
+        assert_eq!(derive_ws_endpoint("not a url"), None);
+
+        let light_client = LightClient::new("http://127.0.0.1:65535");
+        assert!(matches!(light_client.ws_endpoint(), Err(LightNodeError::NoWsEndpoint { .. })));
+        assert_eq!(light_client.with_ws_endpoint("ws://127.0.0.1:9000").ws_endpoint().unwrap(), "ws://127.0.0.1:9000");
+    }
+
+    #[tokio::test]
+    async fn test_get_block_retries_unavailable_block() { 
+        let transport = MemoryTransport::default()
+            .with_error("getBlock", 5, -32004, "Block not available for slot 5")
+            .with_result("getBlock", 5, block_json(5, &[]));
+        let light_client = test_client(transport);
+        let block = light_client.get_block(5).await.unwrap();
+        assert_eq!(block.result.parent_slot, 4);
+        assert_eq!(light_client.transport.calls("getBlock"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_block_gives_up_after_max_attempts() { 
+        let transport = MemoryTransport::default().with_error("getBlock", 5, -32004, "Block not available for slot 5");
+        let light_client = test_client(transport);
+        let err = light_client.get_block(5).await.unwrap_err();
+        assert!(matches!(err, LightNodeError::BlockUnavailable { slot: 5, attempts: 3 }), "{}", err);
+        assert_eq!(light_client.transport.calls("getBlock"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_block_headers() { 
+        let transport = MemoryTransport::default()
+            .with_result("getBlockHeaders", 5, vec![1u8, 2, 3])
+            .with_error("getBlockHeaders", 6, -32601, "Method not found");
+        let light_client = test_client(transport);
+        let resp = light_client.get_block_headers(5, Signature::default()).await.unwrap();
+        assert_eq!(resp.result, vec![1, 2, 3]);
+
+        let err = light_client.get_block_headers(6, Signature::default()).await.unwrap_err();
+        assert!(matches!(err, LightNodeError::BlockHeadersUnsupported { .. }), "{}", err);
+        // method not found is permanent, so isnt retried
+        assert_eq!(light_client.transport.calls("getBlockHeaders"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_block_votes() { 
+        let (validator, other_validator, minority_validator) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let (bank_hash, other_bank_hash) = (Hash::new_unique(), Hash::new_unique());
+        let stakes = [(validator.pubkey(), 60), (other_validator.pubkey(), 30), (minority_validator.pubkey(), 10)];
+        let transport = with_stakes(MemoryTransport::default(), &stakes)
+            .with_result("getBlocks", "", vec![10u64, 11, 12])
+            .with_result("getBlock", 10, block_json(10, &[]))
+            .with_result("getBlock", 11, block_json(11, &[vote_tx(&validator, 10, bank_hash), vote_tx(&minority_validator, 10, other_bank_hash)]))
+            // a vote on another slot and a repeated vote arent counted
+            .with_result("getBlock", 12, block_json(12, &[ 
+                vote_tx(&other_validator, 10, bank_hash),
+                vote_tx(&other_validator, 11, other_bank_hash),
+                vote_tx(&validator, 10, bank_hash),
+            ]));
+        let light_client = test_client(transport);
+        let tally = light_client.parse_block_votes(10, 3).await.unwrap();
+
+        assert_eq!(tally.slot, 10);
+        assert_eq!(tally.total_stake, 100);
+        assert_eq!(tally.stake_for(&bank_hash), 90);
+        assert_eq!(tally.stake_for(&other_bank_hash), 10);
+        assert_eq!(tally.per_bank_hash[&other_bank_hash], vec![(minority_validator.pubkey(), 10)]);
+        assert_eq!(tally.unattributed_votes, 0);
+        assert!(tally.signature_verification_failures.is_empty());
+        assert_eq!(tally.slots_scanned, 3);
+    }
+
+    #[tokio::test]
+    async fn test_scan_votes_skips_unavailable_pending_slots() { 
+        let validator = Keypair::new();
+        let bank_hash = Hash::new_unique();
+        // slot 12 is after the last confirmed block, so still pending
+        let transport = with_stakes(MemoryTransport::default(), &[(validator.pubkey(), 100)])
+            .with_result("getBlocks", "", vec![10u64, 11])
+            .with_result("getBlock", 10, block_json(10, &[]))
+            .with_result("getBlock", 11, block_json(11, &[vote_tx(&validator, 10, bank_hash)]))
+            .with_error("getBlock", 12, -32004, "Block not available for slot 12");
+        let light_client = test_client(transport);
+        let tally = light_client.parse_block_votes(10, 3).await.unwrap();
+        assert_eq!(tally.unavailable_slots, vec![12]);
+        assert_eq!(tally.stake_for(&bank_hash), 100);
+
+        // a confirmed slot which cant be fetched still fails the scan
+        let transport = with_stakes(MemoryTransport::default(), &[(validator.pubkey(), 100)])
+            .with_result("getBlocks", "", vec![10u64, 11])
+            .with_result("getBlock", 10, block_json(10, &[]))
+            .with_error("getBlock", 11, -32004, "Block not available for slot 11");
+        let err = test_client(transport).parse_block_votes(10, 2).await.unwrap_err();
+        assert!(matches!(err, LightNodeError::BlockUnavailable { slot: 11, .. }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_vote_account_stakes_are_cached() { 
+        let validator = Keypair::new();
+        let transport = with_stakes(MemoryTransport::default(), &[(validator.pubkey(), 100)])
+            .with_result("getBlocks", "", vec![10u64, 11])
+            .with_result("getBlock", 10, block_json(10, &[]))
+            .with_result("getBlock", 11, block_json(11, &[]));
+        let light_client = test_client(transport);
+        light_client.parse_block_votes(10, 1).await.unwrap();
+        light_client.parse_block_votes(11, 1).await.unwrap();
+        // the epoch is checked each time, the stakes are fetched once
+        assert_eq!(light_client.transport.calls("getEpochInfo"), 2);
+        assert_eq!(light_client.transport.calls("getVoteAccounts"), 1);
+
+        light_client.refresh_vote_accounts().await.unwrap();
+        assert_eq!(light_client.transport.calls("getVoteAccounts"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_block_votes_decodes_base64_transactions() { 
+        let validator = Keypair::new();
+        let bank_hash = Hash::new_unique();
+        let tx = bincode::serialize(&vote_tx(&validator, 10, bank_hash)).unwrap();
+        let mut block = block_json(10, &[]);
+        block["transactions"] = json!([{ "transaction": [BASE64.encode(tx), "base64"], "meta": null }]);
+        let transport = with_stakes(MemoryTransport::default(), &[(validator.pubkey(), 100)])
+            .with_result("getBlocks", "", vec![10u64])
+            .with_result("getBlock", 10, block);
+        let light_client = test_client(transport).with_block_encoding(BlockEncoding::Base64);
+        let tally = light_client.parse_block_votes(10, 1).await.unwrap();
+        assert_eq!(tally.stake_for(&bank_hash), 100);
+    }
+
+    #[tokio::test]
+    async fn test_block_header_cache_hit_sends_no_request() { 
+        let cache_dir = std::env::temp_dir().join(format!("vote-cache-{}", Hash::new_unique()));
+        let transport = MemoryTransport::default().with_result("getBlockHeaders", 5, vec![1u8, 2, 3]);
+        let light_client = test_client(transport).with_cache_dir(&cache_dir);
+        let signature = Signature::new_unique();
+
+        let resp = light_client.get_block_headers(5, signature).await.unwrap();
+        let cached_resp = light_client.get_block_headers(5, signature).await.unwrap();
+        assert_eq!(cached_resp.result, resp.result);
+        assert_eq!(light_client.transport.calls("getBlockHeaders"), 1);
+
+        light_client.block_header_cache().unwrap().clear().await.unwrap();
+        light_client.get_block_headers(5, signature).await.unwrap();
+        assert_eq!(light_client.transport.calls("getBlockHeaders"), 2);
+        light_client.block_header_cache().unwrap().clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_block_null_result_is_missing() { 
+        let transport = MemoryTransport::default()
+            .with_result("getBlock", 5, serde_json::Value::Null)
+            .with_error("getBlock", 6, -32007, "Slot 6 was skipped, or missing due to ledger jump to recent snapshot");
+        let light_client = test_client(transport);
+        let err = light_client.get_block(5).await.unwrap_err();
+        assert!(matches!(err, LightNodeError::BlockMissing { slot: 5 }), "{}", err);
+        let err = light_client.get_block(6).await.unwrap_err();
+        assert!(matches!(err, LightNodeError::SlotSkipped { slot: 6, code: -32007, .. }), "{}", err);
+        // neither is retried
+        assert_eq!(light_client.transport.calls("getBlock"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_block_headers_response() { 
+        let transport = MemoryTransport::default().with_response("getBlockHeaders", 5, "<html>502 Bad Gateway</html>");
+        let light_client = test_client(transport);
+        let err = light_client.get_block_headers(5, Signature::default()).await.unwrap_err();
+        match err { 
+            LightNodeError::MalformedResponse { method, body, .. } => { 
+                assert_eq!(method, "getBlockHeaders");
+                assert_eq!(body, "<html>502 Bad Gateway</html>");
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_json_rpc_response_parsing() { 
+        let resp: JsonRpcResponse<u64> = serde_json::from_str(r#"{"jsonrpc":"2.0","result":5,"id":1}"#).unwrap();
+        assert_eq!(resp, JsonRpcResponse::Result { jsonrpc: "2.0".to_string(), result: 5, id: RpcId::Num(1) });
+        let resp: JsonRpcResponse<u64> = serde_json::from_str(r#"{"jsonrpc":"2.0","result":5,"id":"a1"}"#).unwrap();
+        assert_eq!(resp.into_result().unwrap(), 5);
+
+        // null is a result of a nullable type
+        let resp: JsonRpcResponse<Option<u64>> = serde_json::from_str(r#"{"jsonrpc":"2.0","result":null,"id":1}"#).unwrap();
+        assert!(matches!(resp, JsonRpcResponse::Result { result: None, .. }));
+
+        let resp: JsonRpcResponse<u64> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request","data":{"x":1}},"id":null}"#
+        ).unwrap();
+        match resp { 
+            JsonRpcResponse::Error { error, id, .. } => { 
+                assert_eq!((error.code, error.message.as_str(), id), (-32600, "Invalid request", None));
+                assert_eq!(error.data, Some(json!({ "x": 1 })));
+            }
+            resp => panic!("unexpected response: {:?}", resp),
+        }
+        let err = serde_json::from_str::<JsonRpcResponse<u64>>(r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"Block not available"},"id":1}"#)
+            .unwrap()
+            .into_result()
+            .unwrap_err();
+        assert!(matches!(err, LightNodeError::JsonRpc { code: -32004, .. }), "{}", err);
+
+        assert!(serde_json::from_str::<JsonRpcResponse<u64>>(r#"{"jsonrpc":"2.0","id":1}"#).is_err());
+        assert!(serde_json::from_str::<JsonRpcResponse<u64>>(r#"{"jsonrpc":"2.0","result":5}"#).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentLevel;
+
+use vote::error::LightNodeError;
+use vote::retry::RetryPolicy;
+use vote::transport::RpcEndpoint;
+
+/// settings read from a toml file with `--config`, for running the light 
+/// node outside the local validator's directory layout. every setting is 
+/// optional, and flags given on the command line override the file's
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config { 
+    /// rpc endpoint (must support the getBlockHeaders method)
+    pub endpoint: Option<String>,
+    /// endpoints to fail over between in priority order, after `endpoint`
+    pub endpoints: Vec<EndpointConfig>,
+    pub ws_endpoint: Option<String>,
+    /// yellowstone geyser grpc endpoint to stream blocks from
+    #[cfg(feature = "geyser")]
+    pub geyser_endpoint: Option<String>,
+    #[cfg(feature = "geyser")]
+    pub geyser_x_token: Option<String>,
+    pub commitment: Option<CommitmentLevel>,
+    pub vote_window: Option<u64>,
+    pub adaptive_vote_window: bool,
+    /// the demo's sender keypair file
+    pub keypair: Option<PathBuf>,
+    /// the demo's recipient keypair file
+    pub recipient: Option<PathBuf>,
+    pub retry: Option<RetryConfig>,
+}
+
+/// an endpoint of `Config::endpoints`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointConfig { 
+    pub url: String,
+    /// whether the endpoint serves `getBlockHeaders`, set to false for a 
+    /// public rpc
+    #[serde(default = "default_block_headers")]
+    pub block_headers: bool,
+}
+
+fn default_block_headers() -> bool { 
+    true
+}
+
+impl From<EndpointConfig> for RpcEndpoint { 
+    fn from(endpoint: EndpointConfig) -> Self { 
+        let rpc_endpoint = RpcEndpoint::new(endpoint.url);
+        if endpoint.block_headers { 
+            rpc_endpoint
+        } else { 
+            rpc_endpoint.without_block_headers()
+        }
+    }
+}
+
+/// the `RetryPolicy` of rpc calls, unset fields keep its defaults
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig { 
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+    pub multiplier: Option<f64>,
+    /// 0 disables the per-attempt timeout
+    pub attempt_timeout_ms: Option<u64>,
+}
+
+impl RetryConfig { 
+    /// the policy, or `LightNodeError::InvalidConfig` for the config at `path` 
+    /// when a value would make retries panic or never run
+    pub fn policy(&self, path: &Path) -> Result<RetryPolicy, LightNodeError> { 
+        let invalid = |message: &str| LightNodeError::InvalidConfig { 
+            path: path.to_path_buf(),
+            message: format!("retry: {}", message),
+        };
+        if self.max_attempts == Some(0) { 
+            return Err(invalid("max_attempts must be at least 1"));
+        }
+        if let Some(multiplier) = self.multiplier { 
+            if !multiplier.is_finite() { 
+                return Err(invalid("multiplier must be finite"));
+            }
+            if multiplier < 1.0 { 
+                return Err(invalid("multiplier must be at least 1.0"));
+            }
+        }
+        let default = RetryPolicy::default();
+        Ok(RetryPolicy { 
+            max_attempts: self.max_attempts.unwrap_or(default.max_attempts),
+            initial_backoff: self.initial_backoff_ms.map(Duration::from_millis).unwrap_or(default.initial_backoff),
+            max_backoff: self.max_backoff_ms.map(Duration::from_millis).unwrap_or(default.max_backoff),
+            multiplier: self.multiplier.unwrap_or(default.multiplier),
+            attempt_timeout: match self.attempt_timeout_ms { 
+                Some(0) => None,
+                Some(ms) => Some(Duration::from_millis(ms)),
+                None => default.attempt_timeout,
+            },
+        })
+    }
+}
+
+impl Config { 
+    pub fn load(path: &Path) -> Result<Self, LightNodeError> { 
+        let contents = std::fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| LightNodeError::InvalidConfig { 
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use super::*;
+
+    fn retry_config(toml: &str) -> RetryConfig { 
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_retry_policy_rejects_invalid_values() { 
+        let path = Path::new("lightnode.toml");
+        for invalid in ["max_attempts = 0", "multiplier = 0.5", "multiplier = nan", "multiplier = inf"] { 
+            let err = retry_config(invalid).policy(path).unwrap_err();
+            assert!(matches!(err, LightNodeError::InvalidConfig { .. }), "{}: {}", invalid, err);
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_keeps_defaults() { 
+        let policy = retry_config("max_attempts = 3\nattempt_timeout_ms = 0").policy(Path::new("lightnode.toml")).unwrap();
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.attempt_timeout, None);
+        assert_eq!(policy.multiplier, RetryPolicy::default().multiplier);
+    }
+}
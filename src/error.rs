@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+#[cfg(feature = "native")]
+use solana_client::client_error::ClientError;
+use solana_sdk::{hash::{Hash, ParseHashError}, pubkey::{ParsePubkeyError, Pubkey}, signature::{ParseSignatureError, Signature}, signer::SignerError, transaction::TransactionError};
+use thiserror::Error;
+#[cfg(feature = "geyser")]
+use yellowstone_grpc_client::GeyserGrpcClientError;
+
+use crate::poh::PohError;
+#[cfg(feature = "block-headers")]
+use crate::verify::VerifyStage;
+use crate::version::ClusterVersion;
+
+// invalid request, method not found and invalid params: the request itself 
+// is wrong, so retrying it wont help
+const PERMANENT_JSON_RPC_ERROR_CODES: [i64; 3] = [-32600, -32601, -32602];
+
+#[derive(Debug, Error)]
+pub enum LightNodeError {
+    #[cfg(any(feature = "native", feature = "wasm"))]
+    #[error("rpc transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[cfg(feature = "native")]
+    #[error("rpc client error: {0}")]
+    RpcClient(Box<ClientError>),
+    #[cfg(feature = "geyser")]
+    #[error("geyser error: {0}")]
+    Geyser(Box<GeyserGrpcClientError>),
+    #[error("rpc error {code}: {message}")]
+    JsonRpc { code: i64, message: String },
+    #[error("{method} returned a null result")]
+    NullResult { method: String },
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("malformed {method} response ({source}): {body}")]
+    MalformedResponse { method: String, source: serde_json::Error, body: String },
+    #[error("block @ slot {slot} unavailable after {attempts} attempts")]
+    BlockUnavailable { slot: u64, attempts: u32 },
+    #[error("tx {signature} unavailable after {attempts} attempts")]
+    TransactionUnavailable { signature: Signature, attempts: u32 },
+    #[error("tx {signature} was dropped: the cluster has no status for it after {attempts} attempts")]
+    TransactionDropped { signature: Signature, attempts: u32 },
+    #[error("tx {signature} expired: its blockhash {blockhash} is too old")]
+    TransactionExpired { signature: Signature, blockhash: Hash },
+    #[error("tx {signature} failed: {err}")]
+    TransactionFailed { signature: Signature, err: TransactionError },
+    #[cfg(feature = "block-headers")]
+    #[error("verification timed out after {timeout:?}: {stage}")]
+    VerifyTimeout { stage: VerifyStage, timeout: Duration },
+    #[error("{method} timed out after {timeout:?}")]
+    Timeout { method: String, timeout: Duration },
+    #[error("{method} was rate limited (http 429), retry after {retry_after:?}")]
+    RateLimited { method: String, retry_after: Option<Duration> },
+    #[error("balance of {pubkey} didnt reach {min_lamports} lamports within {timeout:?}")]
+    BalanceTimeout { pubkey: Pubkey, min_lamports: u64, timeout: Duration },
+    #[error("cluster version {version} is unsupported, expected >= {min} and < {max}")]
+    UnsupportedVersion { version: String, min: ClusterVersion, max: ClusterVersion },
+    #[error("{endpoint} doesnt support getBlockHeaders, use an rpc node running the light node extension")]
+    BlockHeadersUnsupported { endpoint: String },
+    #[error("no rpc endpoints configured (for getBlockHeaders: {block_headers})")]
+    NoEndpoints { block_headers: bool },
+    #[error("cant derive a websocket endpoint from {endpoint}, set one with --ws-endpoint")]
+    NoWsEndpoint { endpoint: String },
+    #[error("no geyser endpoint configured, set one with --geyser-endpoint")]
+    NoGeyserEndpoint,
+    #[error("geyser subscription to {endpoint} closed")]
+    GeyserSubscriptionClosed { endpoint: String },
+    #[error("malformed transaction in the geyser block @ slot {slot}: {message}")]
+    MalformedGeyserTransaction { slot: u64, message: String },
+    #[error("slot {slot} was skipped (rpc error {code}: {message})")]
+    SlotSkipped { slot: u64, code: i64, message: String },
+    #[error("slot {slot} has no block (skipped or pruned)")]
+    BlockMissing { slot: u64 },
+    #[error("block header has no entries, so there is no PoH chain to verify")]
+    EmptyBlockHeader,
+    #[error("slot {slot} has no transactions to request its block headers with")]
+    NoTransactions { slot: u64 },
+    #[error("unsupported transaction encoding: {0}")]
+    UnsupportedTransactionEncoding(String),
+    #[error("bincode error: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("base58 decode error: {0}")]
+    Bs58(#[from] bs58::decode::Error),
+    #[error("base64 decode error: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("invalid pubkey: {0}")]
+    InvalidPubkey(#[from] ParsePubkeyError),
+    #[error("invalid signature: {0}")]
+    InvalidSignature(#[from] ParseSignatureError),
+    #[error("blockhash mismatch @ slot {slot}: getBlock reports {expected}, poh chain ends at {got}")]
+    BlockhashMismatch { slot: u64, expected: Hash, got: Hash },
+    #[error("start blockhash mismatch @ slot {slot}: getBlock reports parent blockhash {expected}, header starts at {got}")]
+    StartBlockhashMismatch { slot: u64, expected: Hash, got: Hash },
+    #[error("signature count mismatch @ slot {slot}: block has {expected} signatures, header says {got}")]
+    SignatureCountMismatch { slot: u64, expected: u64, got: u64 },
+    #[error("invalid hash: {0}")]
+    InvalidHash(#[from] ParseHashError),
+    #[error("slot {slot} has {transactions} transactions, no transaction at index {tx_index}")]
+    TransactionIndexOutOfRange { slot: u64, tx_index: usize, transactions: usize },
+    #[error("block headers of slot {slot} have {entries} entries, no entry at index {entry_index}")]
+    EntryIndexOutOfRange { slot: u64, entry_index: usize, entries: usize },
+    #[error("tx {signature} not included in the block headers")]
+    TxNotIncluded { signature: Signature },
+    #[error("poh verification failed: {0}")]
+    Poh(#[from] PohError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("no recorded {method} response to replay at {}", path.display())]
+    ReplayMissing { method: String, path: std::path::PathBuf },
+    #[error("signing failed: {0}")]
+    Signer(#[from] SignerError),
+    #[error("transaction has no signatures, so it cant be tracked once sent")]
+    UnsignedTransaction,
+    #[error("invalid keypair json: {0}")]
+    InvalidKeypairJson(serde_json::Error),
+    #[error("invalid keypair bytes: {0}")]
+    InvalidKeypairBytes(String),
+    #[error("keypair env var {var} is not set")]
+    MissingKeypairEnv { var: String },
+    #[error("invalid config file {}: {message}", path.display())]
+    InvalidConfig { path: std::path::PathBuf, message: String },
+}
+
+impl LightNodeError { 
+    /// whether the request may succeed if retried, eg. a block which isnt 
+    /// available yet rather than a skipped slot
+    pub fn is_transient(&self) -> bool { 
+        match self { 
+            #[cfg(any(feature = "native", feature = "wasm"))]
+            LightNodeError::Transport(_) => true,
+            #[cfg(feature = "native")]
+            LightNodeError::RpcClient(_) => true,
+            #[cfg(feature = "geyser")]
+            LightNodeError::Geyser(_) => true,
+            LightNodeError::JsonRpc { code, .. } => !PERMANENT_JSON_RPC_ERROR_CODES.contains(code),
+            LightNodeError::NullResult { .. }
+            | LightNodeError::Json(_)
+            | LightNodeError::MalformedResponse { .. }
+            | LightNodeError::GeyserSubscriptionClosed { .. }
+            | LightNodeError::Timeout { .. }
+            | LightNodeError::RateLimited { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl From<ClientError> for LightNodeError { 
+    fn from(e: ClientError) -> Self { 
+        LightNodeError::RpcClient(Box::new(e))
+    }
+}
+
+#[cfg(feature = "geyser")]
+impl From<GeyserGrpcClientError> for LightNodeError { 
+    fn from(e: GeyserGrpcClientError) -> Self { 
+        LightNodeError::Geyser(Box::new(e))
+    }
+}
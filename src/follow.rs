@@ -0,0 +1,248 @@
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::client::{LightClient, MAX_GET_BLOCKS_RANGE};
+use crate::error::LightNodeError;
+#[cfg(feature = "metrics")]
+use crate::metrics::FollowMetrics;
+use crate::transport::RpcTransport;
+use crate::verify::VerificationReport;
+
+// fallback poll interval when no slot notification arrives
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// buffered slot notifications, new ones are dropped while it is full
+const SLOT_CHANNEL_SIZE: usize = 64;
+
+/// progress of `follow`, persisted with `LightClient::with_follow_state_file` 
+/// so a restarted follow resumes where it left off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowState { 
+    /// the last slot verified (or passed over as it has no block)
+    pub last_verified_slot: u64,
+}
+
+impl FollowState { 
+    /// the state in `path`, `None` when the file doesnt exist yet
+    pub async fn load(path: &Path) -> Result<Option<Self>, LightNodeError> { 
+        match tokio::fs::read(path).await { 
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// write the state to `path`, through a temporary file so a crash 
+    /// mid-write doesnt leave it truncated
+    pub async fn save(&self, path: &Path) -> Result<(), LightNodeError> { 
+        let mut tmp_path = PathBuf::from(path).into_os_string();
+        tmp_path.push(".tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(self)?).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+}
+
+impl<T: RpcTransport> LightClient<T> { 
+    /// stream of slots as they are produced, from a `slotSubscribe` websocket 
+    /// subscription on `ws_endpoint`. dropped sockets are reconnected with 
+    /// exponential backoff, and the subscription ends when the stream is dropped
+    pub fn subscribe_slots(&self) -> Result<impl Stream<Item = u64>, LightNodeError> { 
+        let ws_endpoint = self.ws_endpoint()?;
+        let (sender, receiver) = mpsc::channel(SLOT_CHANNEL_SIZE);
+
+        tokio::spawn(async move { 
+            let mut backoff = MIN_BACKOFF;
+            loop { 
+                match PubsubClient::new(&ws_endpoint).await { 
+                    Ok(client) => match client.slot_subscribe().await { 
+                        Ok((mut slots, unsubscribe)) => { 
+                            backoff = MIN_BACKOFF;
+                            while let Some(slot_info) = slots.next().await { 
+                                match sender.try_send(slot_info.slot) { 
+                                    Ok(()) | Err(mpsc::error::TrySendError::Full(_)) => {}
+                                    Err(mpsc::error::TrySendError::Closed(_)) => { 
+                                        unsubscribe().await;
+                                        return;
+                                    }
+                                }
+                            }
+                            warn!(%ws_endpoint, "slot subscription dropped");
+                        }
+                        Err(e) => warn!(%ws_endpoint, "failed to subscribe to slots: {}", e),
+                    },
+                    Err(e) => warn!(%ws_endpoint, "failed to connect: {}", e),
+                }
+                if sender.is_closed() { 
+                    return;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        Ok(stream::unfold(receiver, |mut receiver| async move { 
+            receiver.recv().await.map(|slot| (slot, receiver))
+        }))
+    }
+
+    /// verify every finalized slot from `start_slot` (or the current finalized 
+    /// slot) onwards, sending a report per slot. skipped slots are passed 
+    /// over, and rpc errors are retried with exponential backoff. returns 
+    /// once the receiver is dropped. 
+    /// 
+    /// with a follow state file and no `start_slot`, resumes from the slot 
+    /// after the last verified one, or from the oldest slot the rpc still 
+    /// has when it no longer retains that far back
+    pub async fn follow(&self, start_slot: Option<u64>, reports: mpsc::Sender<VerificationReport>) -> Result<(), LightNodeError> { 
+        self.follow_recording(start_slot, reports, |_| {}, |_| {}).await
+    }
+
+    /// `follow`, counting each report and failure in `metrics`
+    #[cfg(feature = "metrics")]
+    pub async fn follow_with_metrics(&self, start_slot: Option<u64>, reports: mpsc::Sender<VerificationReport>, metrics: &FollowMetrics) -> Result<(), LightNodeError> { 
+        self.follow_recording(
+            start_slot,
+            reports,
+            |report| metrics.record_report(report),
+            |e| metrics.record_error(e),
+        ).await
+    }
+
+    // follow, passing each report and retried error to the callbacks
+    async fn follow_recording<R, E>(&self, start_slot: Option<u64>, reports: mpsc::Sender<VerificationReport>, on_report: R, on_error: E) -> Result<(), LightNodeError> 
+    where 
+        R: Fn(&VerificationReport),
+        E: Fn(&LightNodeError),
+    { 
+        let finalized = CommitmentConfig::finalized();
+        let mut next_slot = match start_slot { 
+            Some(start_slot) => start_slot,
+            None => match self.resume_slot().await? { 
+                Some(resume_slot) => resume_slot,
+                None => self.rpc_client().get_slot_with_commitment(finalized).await?,
+            },
+        };
+        let mut last_verified_slot = None;
+        let mut backoff = MIN_BACKOFF;
+        // new slots wake us up to check the finalized slot, with polling 
+        // as a fallback when the websocket is down
+        let mut slots = Box::pin(self.subscribe_slots()?);
+
+        loop { 
+            let finalized_slot = match self.rpc_client().get_slot_with_commitment(finalized).await { 
+                Ok(finalized_slot) => finalized_slot,
+                Err(e) => { 
+                    warn!(?backoff, "failed to get finalized slot: {}", e);
+                    on_error(&e.into());
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    continue;
+                }
+            };
+            if finalized_slot < next_slot { 
+                let _ = tokio::time::timeout(POLL_INTERVAL, slots.next()).await;
+                continue;
+            }
+
+            // only slots with a block, so skipped slots are never requested. 
+            // far behind, the range is capped to what one getBlocks accepts and 
+            // the rest is caught up on the next iterations
+            let end_slot = finalized_slot.min(next_slot.saturating_add(MAX_GET_BLOCKS_RANGE - 1));
+            let slots = match self.rpc_client().get_blocks_with_commitment(next_slot, Some(end_slot), finalized).await { 
+                Ok(slots) => slots,
+                Err(e) => { 
+                    warn!(next_slot, end_slot, ?backoff, "failed to get blocks: {}", e);
+                    on_error(&e.into());
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    continue;
+                }
+            };
+
+            let mut failed = false;
+            for slot in slots { 
+                let report = match self.verify_block(slot).await { 
+                    Ok(report) => report,
+                    // eg. pruned since it was listed, retrying wont help
+                    Err(LightNodeError::SlotSkipped { .. } | LightNodeError::BlockMissing { .. }) => { 
+                        warn!(slot, "slot has no block, skipping it");
+                        last_verified_slot = Some(slot);
+                        self.save_follow_state(slot).await;
+                        continue;
+                    }
+                    Err(e) => { 
+                        // retry from this slot after backing off
+                        warn!(slot, ?backoff, "failed to verify slot: {}", e);
+                        on_error(&e);
+                        next_slot = slot;
+                        failed = true;
+                        break;
+                    }
+                };
+                on_report(&report);
+                if reports.send(report).await.is_err() { 
+                    return Ok(());
+                }
+                last_verified_slot = Some(slot);
+                self.save_follow_state(slot).await;
+            }
+
+            if failed { 
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            } else { 
+                next_slot = end_slot + 1;
+                backoff = MIN_BACKOFF;
+            }
+            if let Some(last_verified_slot) = last_verified_slot { 
+                next_slot = next_slot.max(last_verified_slot + 1);
+            }
+        }
+    }
+
+    // the slot after the follow state's last verified slot, moved up to the 
+    // oldest slot the rpc still has a block for
+    async fn resume_slot(&self) -> Result<Option<u64>, LightNodeError> { 
+        let state = match self.follow_state_file() { 
+            Some(state_file) => FollowState::load(state_file).await?,
+            None => None,
+        };
+        let state = match state { 
+            Some(state) => state,
+            None => return Ok(None),
+        };
+        let resume_slot = state.last_verified_slot + 1;
+        let first_available_slot = self.rpc_client().get_first_available_block().await?;
+        if resume_slot < first_available_slot { 
+            warn!(
+                resume_slot,
+                first_available_slot,
+                "rpc no longer retains the slots since the last verified one, skipping {} slots",
+                first_available_slot - resume_slot
+            );
+            return Ok(Some(first_available_slot));
+        }
+        info!(resume_slot, "resuming follow");
+        Ok(Some(resume_slot))
+    }
+
+    // a failed write only means a restart resumes from an older slot
+    async fn save_follow_state(&self, last_verified_slot: u64) { 
+        let state_file = match self.follow_state_file() { 
+            Some(state_file) => state_file,
+            None => return,
+        };
+        match (FollowState { last_verified_slot }).save(state_file).await { 
+            Ok(()) => debug!(last_verified_slot, "saved follow state"),
+            Err(e) => warn!(last_verified_slot, "failed to save follow state: {}", e),
+        }
+    }
+}
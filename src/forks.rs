@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, HashSet};
+
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use tracing::{debug, warn};
+
+use crate::block::{decode_block_transactions_with_meta, resolve_account_keys};
+use crate::client::LightClient;
+use crate::error::LightNodeError;
+use crate::transport::RpcTransport;
+use crate::verify::serialize_base58;
+use crate::votes::{vote_candidate_with_slot, verify_vote_candidates, VoteCounter, VoteTally, VoteWindow};
+
+/// a slot whose votes were split between bank hashes, see 
+/// `LightClient::detect_forks`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForkEvent { 
+    pub slot: u64,
+    /// each bank hash with a meaningful share of the stake (see 
+    /// `VoteTally::competing_bank_hashes`), most stake first
+    pub sides: Vec<ForkSide>,
+    /// current and delinquent stake
+    pub total_stake: u128,
+    /// stake which voted on the slot, for any bank hash
+    pub collected_stake: u128,
+}
+
+/// the validators which voted for one of the bank hashes of a `ForkEvent`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForkSide { 
+    #[serde(serialize_with = "serialize_base58")]
+    pub bank_hash: Hash,
+    pub stake: u128,
+    /// most stake first
+    pub validators: Vec<ValidatorVote>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ValidatorVote { 
+    #[serde(serialize_with = "serialize_base58")]
+    pub vote_account: Pubkey,
+    pub stake: u64,
+}
+
+impl ForkEvent { 
+    /// the fork in `vote_tally`'s slot, `None` when the votes agree
+    pub fn from_tally(vote_tally: &VoteTally) -> Option<Self> { 
+        let competing_bank_hashes = vote_tally.competing_bank_hashes();
+        if competing_bank_hashes.is_empty() { 
+            return None;
+        }
+        let sides = competing_bank_hashes
+            .into_iter()
+            .map(|(bank_hash, stake)| { 
+                let mut validators = vote_tally.per_bank_hash
+                    .get(&bank_hash)
+                    .into_iter()
+                    .flatten()
+                    .map(|(vote_account, stake)| ValidatorVote { vote_account: *vote_account, stake: *stake })
+                    .collect::<Vec<_>>();
+                validators.sort_by_key(|x| std::cmp::Reverse(x.stake));
+                ForkSide { bank_hash, stake, validators }
+            })
+            .collect();
+        Some(Self { 
+            slot: vote_tally.slot,
+            sides,
+            total_stake: vote_tally.total_stake,
+            collected_stake: vote_tally.collected_stake(),
+        })
+    }
+}
+
+impl<T: RpcTransport> LightClient<T> { 
+    /// scan the votes on each slot with a block from `start_slot` to 
+    /// `end_slot` (inclusive) over the client's vote window, reporting the 
+    /// slots where meaningful stake voted for more than one bank hash, eg. 
+    /// to alert on a partition or look back at one. each block is fetched 
+    /// once and its votes tallied for the slot they vote on, and slots 
+    /// pruned since they were listed are passed over
+    pub async fn detect_forks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<ForkEvent>, LightNodeError> { 
+        let slots_ahead = match self.vote_window() { 
+            VoteWindow::Fixed(slots_ahead) => slots_ahead,
+            VoteWindow::Adaptive { max_slots } => max_slots,
+        }.max(1);
+        let vote_account_stakes = self.vote_account_stakes().await?;
+        let epoch_schedule = self.epoch_schedule().await?;
+
+        // the window of the last slot runs past `end_slot`
+        let scan_end_slot = end_slot.saturating_add(slots_ahead - 1);
+        let block_slots = self.get_confirmed_blocks(start_slot, scan_end_slot).await?;
+        let target_slots = block_slots.iter()
+            .copied()
+            .filter(|slot| *slot <= end_slot)
+            .collect::<HashSet<_>>();
+        let target_slots = &target_slots;
+
+        let blocks = stream::iter(block_slots.iter().copied())
+            .map(|slot| async move { (slot, self.get_block(slot).await) })
+            .buffer_unordered(self.block_fetch_concurrency())
+            .collect::<Vec<_>>()
+            .await;
+        let mut vote_candidates = vec![];
+        for (slot, resp) in blocks { 
+            let block = match resp { 
+                Ok(resp) => resp.result,
+                Err(LightNodeError::SlotSkipped { .. } | LightNodeError::BlockMissing { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+            for (tx, meta) in decode_block_transactions_with_meta(&block)? { 
+                let account_keys = resolve_account_keys(&tx.message, meta)?;
+                // a vote counts towards the slot it votes on when its block is 
+                // in that slot's window, as with `parse_block_votes`
+                let vote = vote_candidate_with_slot(tx, &account_keys, self.vote_program_id())
+                    .filter(|(voted_slot, _)| target_slots.contains(voted_slot) && slot >= *voted_slot && slot - *voted_slot < slots_ahead);
+                if let Some(vote) = vote { 
+                    vote_candidates.push(vote);
+                }
+            }
+        }
+
+        let verified = verify_vote_candidates(vote_candidates.iter().map(|(_, candidate)| candidate));
+        let mut vote_counters = BTreeMap::<u64, VoteCounter>::new();
+        for ((voted_slot, candidate), verified) in vote_candidates.iter().zip(verified) { 
+            if verified { 
+                vote_counters.entry(*voted_slot).or_default().add(candidate, &vote_account_stakes.stakes);
+            } else { 
+                warn!(voted_slot, vote_account = %candidate.vote_account, "vote signature verification failed");
+            }
+        }
+
+        let mut fork_events = vec![];
+        for (slot, vote_counter) in vote_counters { 
+            let vote_tally = VoteTally { 
+                slot,
+                slot_epoch: epoch_schedule.get_epoch(slot),
+                stake_epoch: vote_account_stakes.epoch,
+                total_stake: vote_account_stakes.total_stake,
+                current_total_stake: vote_account_stakes.current_total_stake,
+                delinquent_stake: vote_account_stakes.delinquent_stake,
+                per_bank_hash: vote_counter.per_bank_hash,
+                unattributed_votes: vote_counter.unattributed_votes,
+                signature_verification_failures: vec![],
+                skipped_slots: vec![],
+                unavailable_slots: vec![],
+                slots_scanned: slots_ahead,
+            };
+            match ForkEvent::from_tally(&vote_tally) { 
+                Some(fork_event) => { 
+                    warn!(slot, sides = fork_event.sides.len(), "votes are split between bank hashes");
+                    fork_events.push(fork_event);
+                }
+                None => debug!(slot, "votes agree"),
+            }
+        }
+        Ok(fork_events)
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    use crate::client::tests::{block_json, test_client, vote_tx, with_stakes};
+    use crate::transport::MemoryTransport;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_detect_forks_fetches_each_block_once() { 
+        let (validator, other_validator) = (Keypair::new(), Keypair::new());
+        let (bank_hash, other_bank_hash, next_bank_hash) = (Hash::new_unique(), Hash::new_unique(), Hash::new_unique());
+        let transport = with_stakes(MemoryTransport::default(), &[(validator.pubkey(), 50), (other_validator.pubkey(), 50)])
+            .with_result("getBlocks", "", vec![10u64, 11, 12])
+            .with_result("getBlock", 10, block_json(10, &[]))
+            .with_result("getBlock", 11, block_json(11, &[vote_tx(&validator, 10, bank_hash), vote_tx(&other_validator, 10, other_bank_hash)]))
+            .with_result("getBlock", 12, block_json(12, &[vote_tx(&validator, 11, next_bank_hash), vote_tx(&other_validator, 11, next_bank_hash)]));
+        let light_client = test_client(transport).with_vote_window(VoteWindow::Fixed(2));
+
+        let fork_events = light_client.detect_forks(10, 11).await.unwrap();
+        assert_eq!(fork_events.len(), 1);
+        assert_eq!(fork_events[0].slot, 10);
+        assert_eq!(fork_events[0].sides.len(), 2);
+        assert_eq!(fork_events[0].collected_stake, 100);
+        assert_eq!(light_client.transport().calls("getBlock"), 3);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use futures::{Stream, StreamExt};
+use solana_sdk::{commitment_config::CommitmentLevel, hash::Hash, instruction::CompiledInstruction, message::{v0, Message, MessageHeader, VersionedMessage}, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use tracing::{debug, warn};
+use yellowstone_grpc_client::{GeyserGrpcClient, GeyserGrpcClientError};
+use yellowstone_grpc_proto::prelude::{self as proto, subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterBlocks, SubscribeUpdate, SubscribeUpdateBlock, SubscribeUpdateTransactionInfo};
+use yellowstone_grpc_proto::tonic::Status;
+
+use crate::client::LightClient;
+use crate::error::LightNodeError;
+use crate::transport::RpcTransport;
+use crate::votes::{bank_hash_stake, verify_vote_candidates, vote_candidate, VoteCounter, VoteProgress, VoteTally};
+
+// name of the subscription's block filter
+const BLOCK_FILTER: &str = "votes";
+
+/// a yellowstone geyser grpc endpoint to stream blocks from, see 
+/// `LightClient::with_geyser`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeyserConfig { 
+    pub endpoint: String,
+    /// the `x-token` header the endpoint authenticates with
+    pub x_token: Option<String>,
+}
+
+impl GeyserConfig { 
+    pub fn new(endpoint: impl Into<String>) -> Self { 
+        Self { endpoint: endpoint.into(), x_token: None }
+    }
+
+    pub fn with_x_token(mut self, x_token: impl Into<String>) -> Self { 
+        self.x_token = Some(x_token.into());
+        self
+    }
+}
+
+impl<T: RpcTransport> LightClient<T> { 
+    /// `parse_block_votes_until` over a geyser block subscription (see 
+    /// `with_geyser`): the blocks after `target_slot` are pushed as they are 
+    /// confirmed rather than polled with `getBlock`, and only their vote 
+    /// transactions are sent. slots confirmed before the subscription 
+    /// started are scanned over rpc. without a `bank_hash` the whole window 
+    /// is scanned, as `parse_block_votes`
+    pub async fn parse_geyser_votes(&self, target_slot: u64, bank_hash: Option<Hash>, max_slots_ahead: u64) -> Result<VoteTally, LightNodeError> { 
+        let geyser = self.geyser().ok_or(LightNodeError::NoGeyserEndpoint)?;
+        let max_end_slot = target_slot + max_slots_ahead.max(1) - 1;
+        let vote_account_stakes = self.vote_account_stakes().await?;
+        let slot_epoch = self.slot_epoch(target_slot, &vote_account_stakes).await?;
+
+        // subscribed before the rpc scan, so no block falls between the two
+        let mut updates = self.subscribe_geyser_blocks(geyser).await?;
+
+        let mut tally = VoteTally { 
+            slot: target_slot,
+            slot_epoch,
+            stake_epoch: vote_account_stakes.epoch,
+            total_stake: vote_account_stakes.total_stake,
+            current_total_stake: vote_account_stakes.current_total_stake,
+            delinquent_stake: vote_account_stakes.delinquent_stake,
+            per_bank_hash: HashMap::new(),
+            unattributed_votes: 0,
+            signature_verification_failures: vec![],
+            skipped_slots: vec![],
+            unavailable_slots: vec![],
+            slots_scanned: 0,
+        };
+        let threshold_stake = tally.threshold_stake(self.supermajority_stake());
+        let total_stake = tally.total_stake;
+        let mut vote_counter = VoteCounter::default();
+        let is_supermajority = |vote_counter: &VoteCounter, slot: u64| match bank_hash { 
+            Some(bank_hash) => { 
+                let progress = VoteProgress { 
+                    slot,
+                    slots_scanned: slot - target_slot + 1,
+                    voted_stake: bank_hash_stake(&vote_counter.per_bank_hash, &bank_hash),
+                    threshold_stake,
+                    total_stake,
+                    supermajority_threshold: self.supermajority_threshold(),
+                };
+                debug!(?progress, "streamed votes");
+                progress.is_supermajority()
+            }
+            None => false,
+        };
+
+        // the first slot not scanned yet
+        let mut next_slot = target_slot;
+        let mut signature_verification_failures = vec![];
+        while next_slot <= max_end_slot { 
+            let block = next_block(&mut updates, &geyser.endpoint, self.timeout()).await?;
+            if block.slot < next_slot { 
+                continue;
+            }
+            if block.slot > next_slot { 
+                let gap_end_slot = (block.slot - 1).min(max_end_slot);
+                if next_slot == target_slot { 
+                    // confirmed before the subscription started
+                    debug!(start_slot = next_slot, end_slot = gap_end_slot, "scanning the slots before the subscription over rpc");
+                    let scanned_votes = self.scan_votes(target_slot, next_slot, gap_end_slot).await?;
+                    for (_, candidate) in scanned_votes.verified.iter() { 
+                        vote_counter.add(candidate, &vote_account_stakes.stakes);
+                    }
+                    signature_verification_failures.extend(scanned_votes.signature_verification_failures.into_iter().map(|(_, vote_account)| vote_account));
+                    tally.skipped_slots.extend(scanned_votes.skipped_slots);
+                    tally.unavailable_slots.extend(scanned_votes.unavailable_slots);
+                } else { 
+                    // blocks are pushed in slot order, so the slots passed over have none
+                    tally.skipped_slots.extend(next_slot..=gap_end_slot);
+                }
+                tally.slots_scanned = gap_end_slot - target_slot + 1;
+                if is_supermajority(&vote_counter, gap_end_slot) { 
+                    break;
+                }
+            }
+            if block.slot > max_end_slot { 
+                break;
+            }
+
+            let slot = block.slot;
+            let mut vote_candidates = vec![];
+            for tx in block.transactions { 
+                let (tx, account_keys) = decode_geyser_transaction(tx)
+                    .map_err(|message| LightNodeError::MalformedGeyserTransaction { slot, message })?;
+                if let Some(candidate) = vote_candidate(tx, &account_keys, target_slot, self.vote_program_id()) { 
+                    vote_candidates.push(candidate);
+                }
+            }
+            let verified = verify_vote_candidates(vote_candidates.iter());
+            for (candidate, verified) in vote_candidates.iter().zip(verified) { 
+                if verified { 
+                    vote_counter.add(candidate, &vote_account_stakes.stakes);
+                } else { 
+                    warn!(slot, vote_account = %candidate.vote_account, "vote signature verification failed");
+                    signature_verification_failures.push(candidate.vote_account);
+                }
+            }
+            tally.slots_scanned = slot - target_slot + 1;
+            next_slot = slot + 1;
+            if is_supermajority(&vote_counter, slot) { 
+                break;
+            }
+        }
+
+        tally.per_bank_hash = vote_counter.per_bank_hash;
+        tally.unattributed_votes = vote_counter.unattributed_votes;
+        tally.signature_verification_failures = signature_verification_failures;
+        Ok(tally)
+    }
+
+    // blocks at `getBlock`'s commitment, with only their vote transactions
+    async fn subscribe_geyser_blocks(&self, geyser: &GeyserConfig) -> Result<impl Stream<Item = Result<SubscribeUpdate, Status>>, LightNodeError> { 
+        let mut client = GeyserGrpcClient::connect_with_timeout(
+            geyser.endpoint.clone(),
+            geyser.x_token.clone(),
+            None,
+            Some(self.connect_timeout()),
+            None,
+            false,
+        ).await?;
+        let commitment = match self.block_commitment() { 
+            CommitmentLevel::Finalized => proto::CommitmentLevel::Finalized,
+            _ => proto::CommitmentLevel::Confirmed,
+        };
+        let block_filter = SubscribeRequestFilterBlocks { 
+            account_include: vec![self.vote_program_id().to_string()],
+            include_transactions: Some(true),
+            include_accounts: Some(false),
+            include_entries: Some(false),
+        };
+        let request = SubscribeRequest { 
+            blocks: HashMap::from([(BLOCK_FILTER.to_string(), block_filter)]),
+            commitment: Some(commitment as i32),
+            ..SubscribeRequest::default()
+        };
+        Ok(client.subscribe_once2(request).await?)
+    }
+}
+
+// the next block of the subscription, passing over pings
+async fn next_block(
+    updates: &mut (impl Stream<Item = Result<SubscribeUpdate, Status>> + Unpin),
+    endpoint: &str,
+    timeout: std::time::Duration,
+) -> Result<SubscribeUpdateBlock, LightNodeError> { 
+    loop { 
+        let update = match tokio::time::timeout(timeout, updates.next()).await { 
+            Ok(Some(update)) => update.map_err(|status| LightNodeError::from(GeyserGrpcClientError::from(status)))?,
+            Ok(None) => return Err(LightNodeError::GeyserSubscriptionClosed { endpoint: endpoint.to_string() }),
+            Err(_) => return Err(LightNodeError::Timeout { method: "geyser block subscription".to_string(), timeout }),
+        };
+        if let Some(UpdateOneof::Block(block)) = update.update_oneof { 
+            return Ok(block);
+        }
+    }
+}
+
+/// a transaction of a geyser block, and its account keys including those 
+/// loaded from lookup tables, as `resolve_account_keys` does for `getBlock`
+pub fn decode_geyser_transaction(tx_info: SubscribeUpdateTransactionInfo) -> Result<(VersionedTransaction, Vec<Pubkey>), String> { 
+    let tx = tx_info.transaction.ok_or("missing transaction")?;
+    let message = tx.message.ok_or("missing message")?;
+    let header = message.header.ok_or("missing message header")?;
+    let header = MessageHeader { 
+        num_required_signatures: u8::try_from(header.num_required_signatures).map_err(|e| e.to_string())?,
+        num_readonly_signed_accounts: u8::try_from(header.num_readonly_signed_accounts).map_err(|e| e.to_string())?,
+        num_readonly_unsigned_accounts: u8::try_from(header.num_readonly_unsigned_accounts).map_err(|e| e.to_string())?,
+    };
+    let account_keys = message.account_keys.iter().map(|key| decode_pubkey(key)).collect::<Result<Vec<_>, _>>()?;
+    let recent_blockhash = <[u8; 32]>::try_from(message.recent_blockhash.as_slice())
+        .map(Hash::new_from_array)
+        .map_err(|_| format!("invalid blockhash of {} bytes", message.recent_blockhash.len()))?;
+    let instructions = message.instructions
+        .into_iter()
+        .map(|ix| Ok(CompiledInstruction { 
+            program_id_index: u8::try_from(ix.program_id_index).map_err(|e| e.to_string())?,
+            accounts: ix.accounts,
+            data: ix.data,
+        }))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut resolved_keys = account_keys.clone();
+    let message = if message.versioned { 
+        let address_table_lookups = message.address_table_lookups
+            .into_iter()
+            .map(|lookup| Ok(v0::MessageAddressTableLookup { 
+                account_key: decode_pubkey(&lookup.account_key)?,
+                writable_indexes: lookup.writable_indexes,
+                readonly_indexes: lookup.readonly_indexes,
+            }))
+            .collect::<Result<Vec<_>, String>>()?;
+        if let Some(meta) = &tx_info.meta { 
+            for key in meta.loaded_writable_addresses.iter().chain(meta.loaded_readonly_addresses.iter()) { 
+                resolved_keys.push(decode_pubkey(key)?);
+            }
+        }
+        VersionedMessage::V0(v0::Message { header, account_keys, recent_blockhash, instructions, address_table_lookups })
+    } else { 
+        VersionedMessage::Legacy(Message { header, account_keys, recent_blockhash, instructions })
+    };
+    let signatures = tx.signatures
+        .iter()
+        .map(|signature| Signature::try_from(signature.as_slice()).map_err(|_| format!("invalid signature of {} bytes", signature.len())))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((VersionedTransaction { signatures, message }, resolved_keys))
+}
+
+fn decode_pubkey(bytes: &[u8]) -> Result<Pubkey, String> { 
+    Pubkey::try_from(bytes).map_err(|_| format!("invalid pubkey of {} bytes", bytes.len()))
+}
+
+#[cfg(test)]
+mod tests { 
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    use crate::client::tests::{test_client, vote_tx};
+    use crate::transport::MemoryTransport;
+
+    use super::*;
+
+    // the protobuf form geyser sends `tx` in
+    fn geyser_transaction(tx: &VersionedTransaction, loaded_addresses: &[Pubkey]) -> SubscribeUpdateTransactionInfo { 
+        let header = tx.message.header();
+        let message = proto::Message { 
+            header: Some(proto::MessageHeader { 
+                num_required_signatures: header.num_required_signatures.into(),
+                num_readonly_signed_accounts: header.num_readonly_signed_accounts.into(),
+                num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts.into(),
+            }),
+            account_keys: tx.message.static_account_keys().iter().map(|key| key.to_bytes().to_vec()).collect(),
+            recent_blockhash: tx.message.recent_blockhash().to_bytes().to_vec(),
+            instructions: tx.message.instructions()
+                .iter()
+                .map(|ix| proto::CompiledInstruction { 
+                    program_id_index: ix.program_id_index.into(),
+                    accounts: ix.accounts.clone(),
+                    data: ix.data.clone(),
+                })
+                .collect(),
+            versioned: !loaded_addresses.is_empty(),
+            address_table_lookups: vec![],
+        };
+        SubscribeUpdateTransactionInfo { 
+            signature: tx.signatures[0].as_ref().to_vec(),
+            is_vote: true,
+            transaction: Some(proto::Transaction { 
+                signatures: tx.signatures.iter().map(|signature| signature.as_ref().to_vec()).collect(),
+                message: Some(message),
+            }),
+            meta: Some(proto::TransactionStatusMeta { 
+                loaded_writable_addresses: loaded_addresses.iter().map(|key| key.to_bytes().to_vec()).collect(),
+                ..proto::TransactionStatusMeta::default()
+            }),
+            index: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_geyser_transaction() { 
+        let validator = Keypair::new();
+        let bank_hash = Hash::new_unique();
+        let tx = vote_tx(&validator, 10, bank_hash);
+
+        let (decoded, account_keys) = decode_geyser_transaction(geyser_transaction(&tx, &[])).unwrap();
+        assert_eq!(decoded, tx);
+        assert_eq!(account_keys, tx.message.static_account_keys());
+        // the decoded message is the one which was signed
+        let candidate = vote_candidate(decoded, &account_keys, 10, &solana_sdk::vote::program::id()).unwrap();
+        assert_eq!(candidate.vote_account, validator.pubkey());
+        assert_eq!(verify_vote_candidates([&candidate].into_iter()), vec![true]);
+    }
+
+    #[test]
+    fn test_decode_geyser_transaction_with_loaded_addresses() { 
+        let loaded_address = Pubkey::new_unique();
+        let tx = vote_tx(&Keypair::new(), 10, Hash::new_unique());
+        let mut tx_info = geyser_transaction(&tx, &[loaded_address]);
+        let (_, account_keys) = decode_geyser_transaction(tx_info.clone()).unwrap();
+        assert_eq!(account_keys.last(), Some(&loaded_address));
+
+        tx_info.transaction.as_mut().unwrap().signatures[0].truncate(10);
+        assert!(decode_geyser_transaction(tx_info).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_geyser_votes_without_endpoint() { 
+        let light_client = test_client(MemoryTransport::default());
+        assert!(matches!(light_client.parse_geyser_votes(10, None, 1).await, Err(LightNodeError::NoGeyserEndpoint)));
+
+        let light_client = light_client.with_geyser(GeyserConfig::new("http://127.0.0.1:10000").with_x_token("token"));
+        assert_eq!(light_client.geyser().unwrap().x_token.as_deref(), Some("token"));
+    }
+}
@@ -0,0 +1,67 @@
+use std::{fs::File, io::Read, path::Path};
+
+use solana_sdk::signature::Keypair;
+
+use crate::error::LightNodeError;
+
+const KEYPAIR_LENGTH: usize = 64;
+
+/// read a keypair from a json file of its 64 bytes, as written by 
+/// `solana-keygen`
+pub fn read_keypair_file<F: AsRef<Path>>(path: F) -> Result<Keypair, LightNodeError> {
+    let mut file = File::open(path.as_ref())?;
+    let mut buf = String::new();
+    file.read_to_string(&mut buf)?;
+    let bytes: Vec<u8> = serde_json::from_str(&buf).map_err(LightNodeError::InvalidKeypairJson)?;
+    Keypair::from_bytes(&bytes[..]).map_err(|e| LightNodeError::InvalidKeypairBytes(e.to_string()))
+}
+
+/// parse a base58 encoded 64 byte keypair (secret then public key)
+pub fn read_keypair_from_base58(s: &str) -> Result<Keypair, LightNodeError> { 
+    let bytes = bs58::decode(s.trim()).into_vec()?;
+    if bytes.len() != KEYPAIR_LENGTH { 
+        return Err(LightNodeError::InvalidKeypairBytes(format!("expected {} bytes, got {}", KEYPAIR_LENGTH, bytes.len())));
+    }
+    Keypair::from_bytes(&bytes).map_err(|e| LightNodeError::InvalidKeypairBytes(e.to_string()))
+}
+
+/// read a base58 encoded keypair from the env var `var`
+pub fn read_keypair_from_env(var: &str) -> Result<Keypair, LightNodeError> { 
+    let value = std::env::var(var).map_err(|_| LightNodeError::MissingKeypairEnv { var: var.to_string() })?;
+    read_keypair_from_base58(&value)
+}
+
+#[cfg(test)]
+mod tests { 
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    #[test]
+    fn test_read_keypair_file() { 
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("keypair-{}.json", keypair.pubkey()));
+        std::fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+        let read = read_keypair_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap().pubkey(), keypair.pubkey());
+
+        assert!(matches!(read_keypair_file(&path), Err(LightNodeError::Io(_))));
+    }
+
+    #[test]
+    fn test_read_keypair_from_base58() { 
+        let keypair = Keypair::new();
+        let read = read_keypair_from_base58(&format!(" {}\n", keypair.to_base58_string())).unwrap();
+        assert_eq!(read.pubkey(), keypair.pubkey());
+
+        let short = bs58::encode([1u8; 32]).into_string();
+        assert!(matches!(read_keypair_from_base58(&short), Err(LightNodeError::InvalidKeypairBytes(_))));
+    }
+
+    #[test]
+    fn test_read_keypair_from_missing_env() { 
+        let err = read_keypair_from_env("SOL_LIGHTNODE_TEST_UNSET_KEYPAIR").unwrap_err();
+        assert!(matches!(err, LightNodeError::MissingKeypairEnv { .. }));
+    }
+}
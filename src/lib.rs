@@ -0,0 +1,33 @@
+pub mod bank_hash;
+#[cfg(feature = "block-headers")]
+pub mod block;
+#[cfg(feature = "native")]
+pub mod cache;
+#[cfg(feature = "native")]
+pub mod client;
+pub mod error;
+#[cfg(feature = "native")]
+pub mod follow;
+#[cfg(feature = "native")]
+pub mod forks;
+#[cfg(feature = "geyser")]
+pub mod geyser;
+pub mod keypair;
+pub mod merkle;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod poh;
+#[cfg(feature = "native")]
+pub mod replay;
+#[cfg(feature = "native")]
+pub mod retry;
+#[cfg(feature = "native")]
+pub mod selftest;
+#[cfg(feature = "native")]
+pub mod transport;
+#[cfg(feature = "block-headers")]
+pub mod verify;
+pub mod version;
+pub mod votes;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,381 +1,316 @@
-use std::{str::FromStr, collections::HashMap, path::Path, fs::File, io::{Read, Write}, thread::sleep, time::Duration};
-
-use serde::{Serialize, Deserialize};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
-use solana_sdk::{vote::{instruction::VoteInstruction, self}, signature::{Signature, Keypair}, transaction::{VersionedTransaction, SanitizedTransaction, Transaction}, pubkey::Pubkey, signer::Signer, system_instruction::{transfer, self}, commitment_config::CommitmentConfig};
-use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding, UiConfirmedBlock, EncodedConfirmedBlock, TransactionBinaryEncoding, BlockHeader, EncodedConfirmedTransactionWithStatusMeta, EntryProof, PartialEntry};
-use solana_account_decoder::{self, UiAccountData, parse_stake::{parse_stake, StakeAccountType}, parse_vote::parse_vote};
-use solana_entry::{entry::{Entry, EntrySlice, hash_transactions, next_hash}, poh::Poh};
-use solana_sdk::hash::Hash;
-use solana_sdk::hash::hashv;
-use solana_merkle_tree::{MerkleTree, merkle_tree::SolidProof};
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use clap::Parser;
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+#[cfg(feature = "geyser")]
+use vote::geyser::GeyserConfig;
+#[cfg(feature = "metrics")]
+use vote::metrics::{serve_metrics, FollowMetrics};
+use tokio::sync::mpsc;
+use tracing_subscriber::EnvFilter;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::{Signature, Keypair}, signer::Signer, system_instruction};
+use vote::{client::{LightClient, DEFAULT_ENDPOINT}, error::LightNodeError, keypair::{read_keypair_file, read_keypair_from_base58, read_keypair_from_env}, transport::{RpcEndpoint, RpcTransport}, verify::{describe_block_header, deserialize_block_header, verify_block_header, TxInclusionProof, VerificationReport}, votes::VoteWindow};
+
+mod cli;
+mod config;
+use config::Config;
+use cli::{Cli, Command, OutputFormat, EXIT_OTHER_ERROR, error_exit_code, inclusion_proof_exit_code, print_error, print_fork_event, print_inclusion_proof, print_json, print_report, print_self_test, report_exit_code};
+
+/// env var the demo reads a base58 keypair from
+const KEYPAIR_ENV_VAR: &str = "SOL_LIGHTNODE_KEYPAIR";
+// how long the demo waits for the recipient's airdrop to land
+const BALANCE_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+// how long the demo's transfer has to land and verify without --verify-timeout
+const DEMO_TIMEOUT: Duration = Duration::from_secs(120);
+// the demo's keypairs in the local validator's directory layout
+const DEFAULT_KEYPAIR_PATH: &str = "./solana/validator/ledger/node1/validator_id.json";
+const DEFAULT_RECIPIENT_PATH: &str = "./solana/validator/ledger/rando_keys/1.json";
+// slots scanned for votes without --vote-window or a config
+const DEFAULT_VOTE_WINDOW: u64 = 5;
+
+/// send a transfer from `keypair` to the keypair at `recipient_path` and 
+/// verify it, see `LightClient::submit_and_verify`
+pub async fn transfer_and_verify<T: RpcTransport>(light_client: &LightClient<T>, keypair: &Keypair, recipient_path: &Path, timeout: Duration) -> Result<VerificationReport, LightNodeError> { 
+    let client = light_client.rpc_client();
+
+    let balance = client.get_balance(&keypair.pubkey()).await?;
+    eprintln!("keypair balance: {:?}", balance);
+
+    let random = read_keypair_file(recipient_path)?;
+    // sometimes takes a while to get the balance from airdrop
+    let balance = light_client.wait_for_balance(&random.pubkey(), 1, BALANCE_WAIT_TIMEOUT).await?;
+    eprintln!("random keypair balance: {:?}", balance);
 
-// from merkle-tree crate
-const LEAF_PREFIX: &[u8] = &[0];
-macro_rules! hash_leaf {
-    {$d:ident} => {
-        hashv(&[LEAF_PREFIX, $d])
-    }
+    // simple tx to verify
+    let ix = system_instruction::transfer(
+        &keypair.pubkey(), 
+        &random.pubkey(), 
+        100
+    );
+    light_client.submit_and_verify(&[ix], &[keypair], timeout).await
 }
 
-#[macro_export]
-macro_rules! send_rpc_call {
-    ($url:expr, $body:expr) => {{
-        use reqwest::header::{ACCEPT, CONTENT_TYPE};
-        let req_client = reqwest::Client::new();
-
-        let res = req_client
-            .post($url)
-            .body($body)
-            .header(CONTENT_TYPE, "application/json")
-            .header(ACCEPT, "application/json")
-            .send()
-            .await
-            .expect("error")
-            .text()
-            .await
-            .expect("error");
-        res
-    }};
+async fn verify_transaction<T: RpcTransport>(light_client: &LightClient<T>, signature: Signature, timeout: Option<Duration>) -> Result<VerificationReport, LightNodeError> { 
+    match timeout { 
+        Some(timeout) => light_client.verify_transaction_with_timeout(signature, timeout).await,
+        None => light_client.verify_transaction(signature).await,
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GetBlockResponse {
-    pub jsonrpc: String,
-    pub result: UiConfirmedBlock,
-    pub id: i64,
+// print the block header's entries to stderr, keeping stdout for the report. 
+// a failed fetch is left for the verification to report
+async fn dump_block_header_entries<T: RpcTransport>(light_client: &LightClient<T>, slot: u64, signature: Signature) { 
+    let block_header = light_client.get_block_headers(slot, signature)
+        .await
+        .and_then(|resp| deserialize_block_header(&resp.result));
+    match block_header { 
+        Ok(block_header) => eprint!("{}", describe_block_header(&block_header)),
+        Err(e) => eprintln!("couldnt dump the block header entries: {}", e),
+    }
 }
 
-async fn get_block(slot: u64, endpoint: &String) -> GetBlockResponse { 
-    let mut block_resp = None;
-    loop { 
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getBlock",
-            "params":[
-                slot,
-                { 
-                    "encoding": "base58", // better for deserialzing
-                    "maxSupportedTransactionVersion": 0,
-                }
-            ]
-        }).to_string();
-        let resp = send_rpc_call!(endpoint, request);
-        let parsed_resp = serde_json::from_str::<GetBlockResponse>(&resp);
-        if parsed_resp.is_err() {  // block is not available yet
-            print!(".");
-            std::io::stdout().flush().unwrap();
-            sleep(Duration::from_millis(500));
-            continue;
+// print an inclusion proof, returning the exit code
+fn print_inclusion_result(result: Result<TxInclusionProof, LightNodeError>, format: OutputFormat) -> i32 { 
+    match result { 
+        Ok(proof) => { 
+            match format { 
+                OutputFormat::Text => print_inclusion_proof(&proof),
+                OutputFormat::Json => print_json(&proof),
+            }
+            inclusion_proof_exit_code(&proof)
+        }
+        Err(e) => { 
+            print_error("verification failed", &e, format);
+            error_exit_code(&e)
         }
-        block_resp = Some(parsed_resp.unwrap());
-        break;
     }
-
-    block_resp.unwrap()
 }
 
-async fn parse_block_votes(target_slot: u64, slots_ahead: u64, endpoint: String) -> Option<(u64, HashMap<Hash, u64>)> {
-    // let endpoint = "https://rpc.helius.xyz/?api-key=cee342ba-0773-41f7-a6e0-9ff01fff124b";
-    let vote_program_id = "Vote111111111111111111111111111111111111111".to_string();
-    let vote_program_id = Pubkey::from_str(&vote_program_id).unwrap();
-
-    let client = RpcClient::new(endpoint.clone());
-    let vote_accounts = client.get_vote_accounts().unwrap();
-    let leader_stakes = vote_accounts.current
-        .iter()
-        .chain(vote_accounts.delinquent.iter())
-        .map(|x| (x.node_pubkey.clone(), x.activated_stake))
-        .collect::<HashMap<_, _>>();
-    let total_stake = leader_stakes.values().sum::<u64>();
-
-    let mut votes = HashMap::new();
-
-    for i in 0..slots_ahead {
-        let slot = target_slot + i;
-
-        println!("requesting block @ slot {}", slot);
-        let resp = get_block(slot, &endpoint).await;
-        let block = resp.result;
-    
-        if block.transactions.is_none() { 
-            println!("no transactions");
-            return None;
-        }
-    
-        for tx in block.transactions.unwrap().iter() {
-            let tx = &tx.transaction;
-            let tx = match tx { 
-                EncodedTransaction::Binary(tx, enc) => {
-                    assert!(*enc == TransactionBinaryEncoding::Base58);
-                    let tx = bs58::decode(tx).into_vec().unwrap();
-                    let tx: VersionedTransaction = bincode::deserialize(&tx[..]).unwrap();
-                    tx
-                }
-                _ => panic!("ahh")
-            };
-    
-            let msg = tx.message;
-            if !msg.static_account_keys().contains(&vote_program_id) { 
-                // println!("tx doesnt include vote program ...");
-                continue;
+#[tokio::main]
+async fn main() {
+    // warnings by default, eg. RUST_LOG=vote=debug for each verification stage. 
+    // logged to stderr so stdout only has the reports
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    tracing_subscriber::fmt().with_env_filter(env_filter).with_writer(std::io::stderr).init();
+
+    let cli = Cli::parse();
+    let config = match &cli.config { 
+        Some(path) => match Config::load(path) { 
+            Ok(config) => config,
+            Err(e) => { 
+                print_error("couldnt load the config", &e, cli.format);
+                std::process::exit(error_exit_code(&e));
             }
-    
-            let ix = msg.instructions().get(0).unwrap();
-            let data = &ix.data;
-            let vote_ix: VoteInstruction = bincode::deserialize(&data[..]).unwrap();
-            let bank_hash = match &vote_ix { 
-                VoteInstruction::Vote(v) => Some(v.hash),   
-                VoteInstruction::CompactUpdateVoteState(v) => Some(v.hash),
-                _ => None
-            };
-            if bank_hash.is_none() { continue; }
-            let bank_hash = bank_hash.unwrap();
-
-            // let slot_vote = vote_ix.last_voted_slot().unwrap_or_default();
-            // println!("{:?}", vote_ix);
-            // println!("voted for slot {:?} with bank_hash {:?}", slot_vote, bank_hash);
-            // println!("{:?} {:?}", node_pubkey, stake_amount);
-    
-            // verify the signature
-            let msg_bytes = msg.serialize();
-            let sig_verifies = tx.signatures
-                .iter()
-                .zip(msg.static_account_keys().iter())
-                .map(|(signature, pubkey)| signature.verify(pubkey.as_ref(), &msg_bytes[..]))
-                .all(|x| x);
-
-            if sig_verifies { 
-                let node_pubkey = msg.static_account_keys().get(0).unwrap().to_string();
-                let stake_amount = leader_stakes.get(&node_pubkey).unwrap();
-
-                let entry = votes.entry(bank_hash).or_insert(0);
-                *entry += stake_amount; 
+        },
+        None => Config::default(),
+    };
+
+    // flags, then the config, then the defaults
+    let endpoint = cli.endpoint
+        .or(config.endpoint)
+        .or_else(|| config.endpoints.first().map(|x| x.url.clone()))
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
+    let commitment = cli.commitment
+        .or(config.commitment.map(|commitment| CommitmentConfig { commitment }))
+        .unwrap_or_else(CommitmentConfig::confirmed);
+    let max_slots = cli.vote_window.or(config.vote_window).unwrap_or(DEFAULT_VOTE_WINDOW);
+    let vote_window = if cli.adaptive_vote_window || config.adaptive_vote_window { 
+        VoteWindow::Adaptive { max_slots }
+    } else { 
+        VoteWindow::Fixed(max_slots)
+    };
+    let mut light_client = LightClient::new(endpoint.clone())
+        .with_commitment(commitment)
+        .with_finalized_only(cli.finalized_only)
+        .with_max_connections(cli.max_connections)
+        .with_supermajority_stake(cli.supermajority_stake)
+        .with_supermajority_threshold(cli.supermajority_threshold)
+        .with_block_encoding(cli.block_encoding)
+        .with_vote_window(vote_window)
+        .with_version_check(cli.version_check);
+    // a retry table is only set when read from `--config`
+    if let (Some(retry), Some(path)) = (&config.retry, &cli.config) { 
+        match retry.policy(path) { 
+            Ok(policy) => light_client = light_client.with_retry_policy(policy),
+            Err(e) => { 
+                print_error("couldnt load the config", &e, cli.format);
+                std::process::exit(error_exit_code(&e));
             }
         }
     }
-
-    Some((total_stake, votes))
-}
-
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GetBlockHeadersResponse {
-    pub jsonrpc: String,
-    pub result: Vec<u8>,
-    pub id: i64,
-}
-
-async fn get_block_headers(slot: u64, signature: Signature, endpoint: String) -> GetBlockHeadersResponse { 
-    let request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getBlockHeaders",
-        "params":[
-            slot, 
-            signature.as_ref(),
-        ]
-    }).to_string();
-    let resp = send_rpc_call!(endpoint, request);
-    let parsed_resp = serde_json::from_str::<GetBlockHeadersResponse>(&resp);
-    if parsed_resp.is_err() { 
-        println!("ERR: {:?}", resp);
+    if let Some(ws_endpoint) = cli.ws_endpoint.or(config.ws_endpoint) { 
+        light_client = light_client.with_ws_endpoint(ws_endpoint);
     }
-    let parsed_resp = parsed_resp.unwrap();
-
-    parsed_resp
-}
-
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GetTransactionResponse {
-    pub jsonrpc: String,
-    pub result: EncodedConfirmedTransactionWithStatusMeta,
-    pub id: i64,
-}
-
-async fn get_tx(signtaure: Signature, endpoint: String) -> GetTransactionResponse { 
-    let mut tx_resp = None;
-
-    while tx_resp.is_none() { 
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getTransaction",
-            "params": [signtaure.to_string(),
-            {
-                "commitment": "confirmed",
-                "encoding": "json",
-            }]
-        }).to_string();
-        let resp = send_rpc_call!(&endpoint, request);
-        let parsed_resp = serde_json::from_str::<GetTransactionResponse>(&resp);
-        if parsed_resp.is_err() {  // tx is not available yet
-            print!(".");
-            sleep(Duration::from_millis(500));
-            continue;
+    #[cfg(feature = "geyser")]
+    if let Some(geyser_endpoint) = cli.geyser_endpoint.or(config.geyser_endpoint) { 
+        let mut geyser = GeyserConfig::new(geyser_endpoint);
+        if let Some(x_token) = cli.geyser_x_token.or(config.geyser_x_token) { 
+            geyser = geyser.with_x_token(x_token);
         }
-
-        tx_resp = Some(parsed_resp.unwrap());
+        light_client = light_client.with_geyser(geyser);
     }
-    print!("\n");
-
-    tx_resp.unwrap()
-}
-
-pub fn next_hash_with_tx_hash(
-    start_hash: &Hash,
-    num_hashes: u64,
-    transaction_hash: Option<Hash>,
-) -> Hash {
-    if num_hashes == 0 && transaction_hash.is_none() {
-        return *start_hash;
+    if let Some(cache_dir) = cli.cache_dir { 
+        light_client = light_client.with_cache_dir(cache_dir);
     }
-
-    let mut poh = Poh::new(*start_hash, None);
-    poh.hash(num_hashes.saturating_sub(1));
-    if transaction_hash.is_none() {
-        poh.tick().unwrap().hash
-    } else {
-        poh.record(transaction_hash.unwrap()).unwrap().hash
+    if let Some(vote_program_id) = cli.vote_program_id { 
+        light_client = light_client.with_vote_program_id(vote_program_id);
     }
-}
-
-pub fn read_keypair_file<F: AsRef<Path>>(path: F) -> Keypair {
-    let mut file = File::open(path.as_ref()).unwrap();
-    let mut buf = String::new();
-    file.read_to_string(&mut buf).unwrap();
-    let bytes: Vec<u8> = serde_json::from_str(&buf).unwrap();
-    Keypair::from_bytes(&bytes[..]).unwrap()
-}
 
-pub async fn verify_slot() { 
-    let endpoint = "http://127.0.0.1:8002";
-    let client = RpcClient::new(endpoint);
-
-    let path = "./solana/validator/ledger/node1/validator_id.json";
-    let keypair = read_keypair_file(path);
-    let balance = client.get_balance(&keypair.pubkey()).unwrap();
-    println!("keypair balance: {:?}", balance);
-
-    let path = "./solana/validator/ledger/rando_keys/1.json";
-    let random = read_keypair_file(path);
-    let mut balance = 0;
-    // sometimes takes a while to get the balance from airdrop
-    while balance == 0 { 
-        balance = client.get_balance(&random.pubkey()).unwrap();
-        sleep(Duration::from_millis(500));
+    let mut command = cli.command;
+    if let Command::Demo { keypair, recipient, .. } = &mut command { 
+        *keypair = keypair.take().or(config.keypair);
+        *recipient = recipient.take().or(config.recipient);
     }
-    println!("random keypair balance: {:?}", balance);
 
-    // simple tx to verify
-    let ix = system_instruction::transfer(
-        &keypair.pubkey(), 
-        &random.pubkey(), 
-        100
-    );
-    let recent_blockhash = client.get_latest_blockhash().expect("Failed to get latest blockhash.");
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&keypair.pubkey()), &[&keypair], recent_blockhash);
-    let tx_sig = client.send_transaction(&tx).unwrap();
-    let tx_info = get_tx(tx_sig, endpoint.to_string()).await; 
-    let slot = tx_info.result.slot;
-    println!("verifying slot {:?}", slot);
+    // the config's failover endpoints follow the endpoint, unless it is 
+    // already the first of them
+    let mut endpoints = config.endpoints.into_iter().map(|x| x.into()).collect::<Vec<RpcEndpoint>>();
+    if !endpoints.is_empty() && endpoints[0].url != endpoint { 
+        endpoints.insert(0, RpcEndpoint::new(endpoint));
+    }
 
-    // get headers
-    let block_headers = get_block_headers(slot, tx_sig, endpoint.to_string()).await.result;
-    let block_headers: BlockHeader = bincode::deserialize(&block_headers).unwrap();
-    let entries = block_headers.entries; 
+    let verify_timeout = cli.verify_timeout.map(Duration::from_secs);
+    match (cli.replay_dir, cli.record_dir) { 
+        (Some(replay_dir), _) => run(light_client.with_replay_dir(replay_dir), command, verify_timeout, cli.format, cli.verbose, cli.allow_unconfirmed).await,
+        (None, Some(record_dir)) => run(light_client.with_record_dir(record_dir), command, verify_timeout, cli.format, cli.verbose, cli.allow_unconfirmed).await,
+        (None, None) if !endpoints.is_empty() => run(light_client.with_endpoints(endpoints), command, verify_timeout, cli.format, cli.verbose, cli.allow_unconfirmed).await,
+        (None, None) => run(light_client, command, verify_timeout, cli.format, cli.verbose, cli.allow_unconfirmed).await,
+    }
+}
 
-    // find and verify tx signature in entry
-    let mut tx_found = false;
-    for entry in entries.iter() {
-        match entry { 
-            EntryProof::MerkleEntry(x) => {
-                println!("{:?}", x);
+// run the command with the client, over whichever transport it was given
+async fn run<T: RpcTransport>(mut light_client: LightClient<T>, command: Command, verify_timeout: Option<Duration>, format: OutputFormat, verbose: bool, allow_unconfirmed: bool) { 
+    // verify-header doesnt check votes
+    let checks_votes = !matches!(command, Command::VerifyHeader { .. });
 
-                // verify merkle proof here 
-                let leaf = tx_sig.as_ref();
-                let candidate = hash_leaf!(leaf);
-                // when len == 1 this does nothing
-                let verified = x.proof.verify(candidate);
-                if !verified { 
-                    println!("tx signature not verified!");
-                    return;
+    let result = match command { 
+        Command::VerifySlot { slot, signature, dump_entries } => { 
+            if dump_entries { 
+                dump_block_header_entries(&light_client, slot, signature).await;
+            }
+            match verify_timeout { 
+                Some(timeout) => light_client.verify_slot_with_timeout(slot, signature, timeout).await,
+                None => light_client.verify_slot(slot, signature).await,
+            }
+        }
+        Command::VerifyTx { signature } => { 
+            verify_transaction(&light_client, signature, verify_timeout).await
+        }
+        Command::VerifyInclusion { signature } => { 
+            let result = light_client.verify_inclusion(signature).await;
+            std::process::exit(print_inclusion_result(result, format));
+        }
+        Command::VerifyInclusionByIndex { slot, entry_index, tx_index } => { 
+            let result = light_client.verify_inclusion_by_index(slot, entry_index, tx_index).await;
+            std::process::exit(print_inclusion_result(result, format));
+        }
+        Command::VerifyHeader { slot, signature, header_file, epoch_accounts_hash, signature_count, dump_entries } => { 
+            std::fs::read(header_file)
+                .map_err(LightNodeError::from)
+                .and_then(|bytes| deserialize_block_header(&bytes))
+                .and_then(|block_header| { 
+                    if dump_entries { 
+                        eprint!("{}", describe_block_header(&block_header));
+                    }
+                    verify_block_header(slot, &block_header, epoch_accounts_hash, signature_count, signature)
+                })
+        }
+        Command::Selftest => { 
+            let report = light_client.self_test().await;
+            match format { 
+                OutputFormat::Text => print_self_test(&report),
+                OutputFormat::Json => print_json(&report),
+            }
+            std::process::exit(if report.passed() { 0 } else { EXIT_OTHER_ERROR });
+        }
+        Command::DetectForks { start_slot, end_slot } => { 
+            match light_client.detect_forks(start_slot, end_slot).await { 
+                Ok(fork_events) => { 
+                    for fork_event in fork_events.iter() { 
+                        match format { 
+                            OutputFormat::Text => print_fork_event(fork_event),
+                            OutputFormat::Json => print_json(fork_event),
+                        }
+                    }
+                    if fork_events.is_empty() && format == OutputFormat::Text { 
+                        println!("no forks between slots {} and {}", start_slot, end_slot);
+                    }
+                    std::process::exit(0);
                 }
-
-                tx_found = true;
-                println!("tx signature verified!");
-                break;
-            }, 
-            _ => {}
-        };
-    }
-    if !tx_found { 
-        println!("tx signature not found in entries...");
-        return;
-    }
-
-    // verify the entries are valid PoH ticks / path 
-    let start_blockhash = block_headers.start_blockhash;
-    let genesis = [EntryProof::PartialEntry(PartialEntry {
-        num_hashes: 0,
-        hash: start_blockhash,
-        transaction_hash: None
-    })];
-    let mut entry_pairs = genesis.iter().chain(entries.iter()).zip(entries.iter());
-    let verified = entry_pairs.all(|(x0, x1)| {
-        let start_hash = x0.hash();
-        let r = match x1 { 
-            EntryProof::PartialEntry(x) => {
-                next_hash_with_tx_hash(&start_hash, x.num_hashes, x.transaction_hash) == x.hash
-            }, 
-            EntryProof::MerkleEntry(x) => {
-                let tx_hash = if let Some(hash) = x.proof.root() {
-                    hash
-                } else { 
-                    let tx_sig_ref = tx_sig.as_ref();
-                    hash_leaf!(tx_sig_ref)
+                Err(e) => { 
+                    print_error("fork detection failed", &e, format);
+                    std::process::exit(error_exit_code(&e));
+                }
+            }
+        }
+        Command::Follow { start_slot, state_file, #[cfg(feature = "metrics")] metrics_addr } => { 
+            if let Some(state_file) = state_file { 
+                light_client = light_client.with_follow_state_file(state_file);
+            }
+            let (sender, mut receiver) = mpsc::channel(16);
+            let print_reports = async { 
+                while let Some(report) = receiver.recv().await { 
+                    match format { 
+                        OutputFormat::Text => { 
+                            print_report(&report, verbose);
+                            println!();
+                        }
+                        OutputFormat::Json => print_json(&report),
+                    }
+                }
+            };
+            #[cfg(feature = "metrics")]
+            let follow = async { 
+                let metrics_addr = match metrics_addr { 
+                    Some(metrics_addr) => metrics_addr,
+                    None => return light_client.follow(start_slot, sender).await,
                 };
-                next_hash_with_tx_hash(&start_hash, x.num_hashes, Some(tx_hash)) == x.hash
+                let metrics = Arc::new(FollowMetrics::new());
+                let server = tokio::spawn(serve_metrics(metrics_addr, metrics.clone()));
+                tokio::select! { 
+                    result = light_client.follow_with_metrics(start_slot, sender, &metrics) => result,
+                    // eg. the address is in use
+                    Ok(Err(e)) = server => Err(e),
+                }
+            };
+            #[cfg(not(feature = "metrics"))]
+            let follow = light_client.follow(start_slot, sender);
+            let (result, _) = tokio::join!(follow, print_reports);
+            if let Err(e) = result { 
+                print_error("follow failed", &e, format);
+                std::process::exit(error_exit_code(&e));
             }
-        };
-        r
-    });
-    if !verified { 
-        println!("entry verification failed ...");
-        return;
-    }
-    println!("entry verification passed!");
-
-    // recompute the bank hash 
-    let last_blockhash = entries.last().unwrap().hash();
-    let bankhash = hashv(&[
-        block_headers.parent_hash.as_ref(),
-        block_headers.accounts_delta_hash.as_ref(),
-        block_headers.signature_count_buf.as_ref(), 
-        last_blockhash.as_ref()
-    ]);
-    println!("bank hash: {:?}", bankhash);
-
-    println!("parsing votes from block ...");
-    let vote_result = parse_block_votes(slot, 5, endpoint.to_string()).await;
-    if vote_result.is_none() { 
-        println!("vote verification failed ...");
+            return;
+        }
+        Command::Demo { keypair, keypair_base58, recipient } => { 
+            // --keypair-base58, then the env var, then the keypair file
+            let keypair = match keypair_base58 { 
+                Some(keypair_base58) => read_keypair_from_base58(&keypair_base58),
+                None if std::env::var_os(KEYPAIR_ENV_VAR).is_some() => read_keypair_from_env(KEYPAIR_ENV_VAR),
+                None => read_keypair_file(keypair.unwrap_or_else(|| PathBuf::from(DEFAULT_KEYPAIR_PATH))),
+            };
+            match keypair { 
+                Ok(keypair) => { 
+                    let timeout = verify_timeout.unwrap_or(DEMO_TIMEOUT);
+                    let recipient = recipient.unwrap_or_else(|| PathBuf::from(DEFAULT_RECIPIENT_PATH));
+                    transfer_and_verify(&light_client, &keypair, &recipient, timeout).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+    };
+    match result { 
+        Ok(report) => { 
+            match format { 
+                OutputFormat::Text => print_report(&report, verbose),
+                OutputFormat::Json => print_json(&report),
+            }
+            std::process::exit(report_exit_code(&report, checks_votes, allow_unconfirmed));
+        }
+        Err(e) => { 
+            print_error("verification failed", &e, format);
+            std::process::exit(error_exit_code(&e));
+        }
     }
-    let (total_stake, votes) = vote_result.unwrap();
-    let bankhash_vote_stakes = votes.get(&bankhash).unwrap();
-    println!("bankhash vote stakes: {:?} total stakes: {total_stake:?}", bankhash_vote_stakes);
-
-    // bankhash_vote_stakes >= 2/3 * total_stake
-    // 3 * bankhash_vote_stakes >= 2 * total_stake
-    let is_supermajority = 3 * bankhash_vote_stakes >= 2 * total_stake;
-    println!("bankhash has supermajority of votes: {:?}", is_supermajority);
-}
-
-#[tokio::main]
-async fn main() {
-    verify_slot().await;
 }
@@ -0,0 +1,34 @@
+use solana_merkle_tree::merkle_tree::SolidProof;
+use solana_sdk::{hash::{Hash, hashv}, signature::Signature};
+
+/// prefix of leaf hashes (from merkle-tree crate)
+pub const LEAF_PREFIX: &[u8] = &[0];
+
+pub fn hash_leaf(leaf: &[u8]) -> Hash { 
+    hashv(&[LEAF_PREFIX, leaf])
+}
+
+/// the merkle leaf of a transaction in its entry: `hashv(LEAF_PREFIX, 
+/// signature)`, where `signature` is the transaction's first signature (its 
+/// id). the transaction's other signatures arent part of the leaf
+pub fn transaction_leaf_hash(signature: &Signature) -> Hash { 
+    hash_leaf(signature.as_ref())
+}
+
+/// verify `leaf` is included in the tree `proof` was built from. a single 
+/// leaf proof has no branches, so this accepts any leaf: check its root with 
+/// `verify_merkle_inclusion_at` instead
+pub fn verify_merkle_inclusion(leaf: &[u8], proof: &SolidProof) -> bool { 
+    proof.verify(hash_leaf(leaf))
+}
+
+/// verify `leaf` is included in the tree with root `expected_root`. a single 
+/// leaf proof has no root, so the leaf hash itself is the root
+pub fn verify_merkle_inclusion_at(leaf: &[u8], proof: &SolidProof, expected_root: Hash) -> bool { 
+    inclusion_root(leaf, proof.root()) == expected_root && verify_merkle_inclusion(leaf, proof)
+}
+
+// the root `leaf` is proven against, given its proof's root
+fn inclusion_root(leaf: &[u8], proof_root: Option<Hash>) -> Hash { 
+    proof_root.unwrap_or_else(|| hash_leaf(leaf))
+}
@@ -0,0 +1,123 @@
+use std::{fmt::Write, net::SocketAddr, sync::{Arc, atomic::{AtomicU64, Ordering}}, time::Duration};
+
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+use tracing::{debug, warn};
+
+use crate::error::LightNodeError;
+use crate::verify::VerificationReport;
+
+/// counters and gauges of a `follow`, served in the prometheus text format 
+/// by `serve_metrics`
+#[derive(Debug, Default)]
+pub struct FollowMetrics { 
+    slots_verified_total: AtomicU64,
+    poh_failures_total: AtomicU64,
+    bank_hash_mismatches_total: AtomicU64,
+    supermajority_achieved_total: AtomicU64,
+    // f64 bits of the last slot's voted stake / threshold stake
+    voted_stake_ratio: AtomicU64,
+    rpc_errors_total: AtomicU64,
+}
+
+impl FollowMetrics { 
+    pub fn new() -> Self { 
+        Self::default()
+    }
+
+    /// count a verified slot
+    pub fn record_report(&self, report: &VerificationReport) { 
+        self.slots_verified_total.fetch_add(1, Ordering::Relaxed);
+        if !report.poh_verified { 
+            self.poh_failures_total.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if report.votes_for_other_bank_hash() { 
+            self.bank_hash_mismatches_total.fetch_add(1, Ordering::Relaxed);
+        }
+        if report.is_supermajority { 
+            self.supermajority_achieved_total.fetch_add(1, Ordering::Relaxed);
+        }
+        self.voted_stake_ratio.store(report.voted_stake_ratio.to_bits(), Ordering::Relaxed);
+    }
+
+    /// count a slot which failed to verify
+    pub fn record_error(&self, error: &LightNodeError) { 
+        let counter = match error { 
+            LightNodeError::Poh(_) => &self.poh_failures_total,
+            LightNodeError::BlockhashMismatch { .. }
+            | LightNodeError::StartBlockhashMismatch { .. }
+            | LightNodeError::SignatureCountMismatch { .. } => &self.bank_hash_mismatches_total,
+            // follow only gives up on a slot over rpc errors
+            _ => &self.rpc_errors_total,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// the metrics in the prometheus text exposition format
+    pub fn render(&self) -> String { 
+        let counters = [
+            ("slots_verified_total", "slots verified", &self.slots_verified_total),
+            ("poh_failures_total", "slots whose PoH chain failed to verify", &self.poh_failures_total),
+            ("bank_hash_mismatches_total", "slots whose votes or block dont match the recomputed bank hash", &self.bank_hash_mismatches_total),
+            ("supermajority_achieved_total", "slots whose bank hash has a supermajority of votes", &self.supermajority_achieved_total),
+            ("rpc_errors_total", "rpc errors while following", &self.rpc_errors_total),
+        ];
+        let mut out = String::new();
+        for (name, help, counter) in counters { 
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, counter.load(Ordering::Relaxed));
+        }
+        let voted_stake_ratio = f64::from_bits(self.voted_stake_ratio.load(Ordering::Relaxed));
+        let _ = writeln!(out, "# HELP voted_stake_ratio voted stake over the threshold stake of the last verified slot");
+        let _ = writeln!(out, "# TYPE voted_stake_ratio gauge");
+        let _ = writeln!(out, "voted_stake_ratio {}", voted_stake_ratio);
+        out
+    }
+}
+
+// requests are small, only the request line is looked at
+const MAX_REQUEST_LEN: usize = 4096;
+// so a persistent accept error doesnt spin
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// serve `metrics` at `http://<addr>/metrics` until the task is dropped
+pub async fn serve_metrics(addr: SocketAddr, metrics: Arc<FollowMetrics>) -> Result<(), LightNodeError> { 
+    let listener = TcpListener::bind(addr).await?;
+    debug!(%addr, "serving metrics");
+    loop { 
+        // eg. out of file descriptors, which passes once connections close
+        let (mut stream, peer) = match listener.accept().await { 
+            Ok(accepted) => accepted,
+            Err(e) => { 
+                warn!(%addr, "failed to accept metrics connection: {}", e);
+                tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                continue;
+            }
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move { 
+            let mut request = vec![0; MAX_REQUEST_LEN];
+            let len = match stream.read(&mut request).await { 
+                Ok(len) => len,
+                Err(e) => { 
+                    warn!(%peer, "failed to read metrics request: {}", e);
+                    return;
+                }
+            };
+            let response = if request[..len].starts_with(b"GET /metrics ") { 
+                let body = metrics.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else { 
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+            if let Err(e) = stream.write_all(response.as_bytes()).await { 
+                warn!(%peer, "failed to write metrics response: {}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,288 @@
+use solana_sdk::hash::{hash, hashv, Hash};
+#[cfg(feature = "block-headers")]
+use solana_sdk::signature::Signature;
+#[cfg(feature = "block-headers")]
+use solana_transaction_status::EntryProof;
+use std::fmt;
+
+use thiserror::Error;
+
+#[cfg(feature = "block-headers")]
+use crate::merkle::transaction_leaf_hash;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PohError {
+    #[error("{kind} entry {index} does not hash from the previous entry: header has {expected}, recomputed {got}")]
+    EntryMismatch { index: usize, expected: Hash, got: Hash, kind: EntryKind },
+    #[error("entry {index} holds a single transaction whose signature is needed to verify it")]
+    UnknownTransactionHash { index: usize },
+}
+
+/// the kind of `EntryProof` an entry was sent as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind { 
+    /// an entry without the transaction being proven
+    Partial,
+    /// the entry holding the transaction, with its merkle proof
+    Merkle,
+}
+
+#[cfg(feature = "block-headers")]
+impl EntryKind { 
+    pub fn of(entry: &EntryProof) -> Self { 
+        match entry { 
+            EntryProof::PartialEntry(_) => EntryKind::Partial,
+            EntryProof::MerkleEntry(_) => EntryKind::Merkle,
+        }
+    }
+}
+
+impl fmt::Display for EntryKind { 
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { 
+        match self { 
+            EntryKind::Partial => write!(f, "partial"),
+            EntryKind::Merkle => write!(f, "merkle"),
+        }
+    }
+}
+
+/// the next PoH hash after `num_hashes` hashes, mixing in `transaction_hash` 
+/// (the merkle root of all the entry's transaction signatures) or ticking 
+/// when the entry has no transactions
+pub fn next_hash_with_tx_hash(
+    start_hash: &Hash,
+    num_hashes: u64,
+    transaction_hash: Option<Hash>,
+) -> Hash {
+    if num_hashes == 0 && transaction_hash.is_none() {
+        return *start_hash;
+    }
+
+    // as `solana_entry::poh::Poh` hashes, the last hash being the record or 
+    // tick. done here so the crate doesnt depend on solana-entry
+    let mut poh_hash = *start_hash;
+    for _ in 1..num_hashes { 
+        poh_hash = hash(poh_hash.as_ref());
+    }
+    match transaction_hash { 
+        Some(transaction_hash) => hashv(&[poh_hash.as_ref(), transaction_hash.as_ref()]),
+        None => hash(poh_hash.as_ref()),
+    }
+}
+
+/// an entry as hashed into PoH. `transaction_hash` is the merkle root of 
+/// however many transactions the entry holds, `None` for a tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PohEntry { 
+    pub num_hashes: u64,
+    pub transaction_hash: Option<Hash>,
+    pub hash: Hash,
+}
+
+impl PohEntry { 
+    /// a merkle entry only carries the proof for `tx_sig`: with more than 
+    /// one transaction its root is the mixed in hash, otherwise the entry 
+    /// holds just `tx_sig` and its leaf hash was mixed in
+    #[cfg(feature = "block-headers")]
+    pub fn from_proof(entry: &EntryProof, tx_sig: &Signature) -> Self { 
+        match entry { 
+            EntryProof::PartialEntry(x) => Self { 
+                num_hashes: x.num_hashes,
+                transaction_hash: x.transaction_hash,
+                hash: x.hash,
+            },
+            EntryProof::MerkleEntry(x) => Self { 
+                num_hashes: x.num_hashes,
+                transaction_hash: Some(x.proof.root().unwrap_or_else(|| transaction_leaf_hash(tx_sig))),
+                hash: x.hash,
+            },
+        }
+    }
+
+    /// like `from_proof`, but without a signature a merkle entry holding a 
+    /// single transaction cant be resolved (`None`)
+    #[cfg(feature = "block-headers")]
+    pub fn from_entry(entry: &EntryProof) -> Option<Self> { 
+        match entry { 
+            EntryProof::MerkleEntry(x) if x.proof.root().is_none() => None,
+            EntryProof::PartialEntry(x) => Some(Self { 
+                num_hashes: x.num_hashes,
+                transaction_hash: x.transaction_hash,
+                hash: x.hash,
+            }),
+            EntryProof::MerkleEntry(x) => Some(Self { 
+                num_hashes: x.num_hashes,
+                transaction_hash: x.proof.root(),
+                hash: x.hash,
+            }),
+        }
+    }
+
+    pub fn is_tick(&self) -> bool { 
+        self.transaction_hash.is_none()
+    }
+
+    /// whether the entry hashes from `prev_hash`
+    pub fn verify(&self, prev_hash: &Hash) -> bool { 
+        next_hash_with_tx_hash(prev_hash, self.num_hashes, self.transaction_hash) == self.hash
+    }
+}
+
+/// verify the entries form a PoH chain starting from `start_hash`, 
+/// returning the hash of the last entry. merkle entries without a root only 
+/// hold `tx_sig`, so its leaf hash is what was mixed in
+#[cfg(feature = "block-headers")]
+pub fn verify_entry_chain(start_hash: Hash, entries: &[EntryProof], tx_sig: &Signature) -> Result<Hash, PohError> { 
+    verify_chain(start_hash, entries, |_, entry| Ok(PohEntry::from_proof(entry, tx_sig)))
+}
+
+/// verify the entries form a PoH chain starting from `start_hash`, 
+/// returning the final hash, without a transaction to prove. fails on a 
+/// merkle entry holding a single transaction, as its mixed in hash is the 
+/// leaf hash of a signature the header doesnt include. 
+/// 
+/// `start_hash` is the block's start blockhash to verify a whole block, but 
+/// can be the hash of any entry to verify the chain from there on: the 
+/// first entry's `num_hashes` are counted from `start_hash`
+#[cfg(feature = "block-headers")]
+pub fn verify_poh_ticks(start_hash: Hash, entries: &[EntryProof]) -> Result<Hash, PohError> { 
+    verify_chain(start_hash, entries, |index, entry| { 
+        PohEntry::from_entry(entry).ok_or(PohError::UnknownTransactionHash { index })
+    })
+}
+
+#[cfg(feature = "block-headers")]
+fn verify_chain<F>(start_hash: Hash, entries: &[EntryProof], resolve: F) -> Result<Hash, PohError> 
+where 
+    F: Fn(usize, &EntryProof) -> Result<PohEntry, PohError>,
+{ 
+    let mut prev_hash = start_hash;
+    for (index, entry_proof) in entries.iter().enumerate() { 
+        let entry = resolve(index, entry_proof)?;
+        let got = next_hash_with_tx_hash(&prev_hash, entry.num_hashes, entry.transaction_hash);
+        if got != entry.hash { 
+            return Err(PohError::EntryMismatch { index, expected: entry.hash, got, kind: EntryKind::of(entry_proof) });
+        }
+        prev_hash = entry.hash;
+    }
+
+    Ok(prev_hash)
+}
+
+#[cfg(test)]
+mod tests { 
+    #[cfg(feature = "block-headers")]
+    use serde_json::json;
+    // solana-entry doesnt build for wasm32
+    #[cfg(not(target_arch = "wasm32"))]
+    use solana_entry::entry::{hash_transactions, next_hash, Entry};
+    #[cfg(not(target_arch = "wasm32"))]
+    use solana_sdk::{pubkey::Pubkey, system_transaction, transaction::VersionedTransaction};
+    #[cfg(any(feature = "block-headers", not(target_arch = "wasm32")))]
+    use solana_sdk::signature::Keypair;
+    #[cfg(feature = "block-headers")]
+    use solana_sdk::signature::Signer;
+
+    #[cfg(feature = "block-headers")]
+    use crate::merkle::hash_leaf;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::merkle::transaction_leaf_hash;
+
+    use super::*;
+
+    // built from its serde form, which names the variant rather than the 
+    // entry struct
+    #[cfg(feature = "block-headers")]
+    fn partial_entry(num_hashes: u64, hash: Hash, transaction_hash: Option<Hash>) -> EntryProof { 
+        serde_json::from_value(json!({ 
+            "PartialEntry": { "num_hashes": num_hashes, "hash": hash, "transaction_hash": transaction_hash },
+        })).unwrap()
+    }
+
+    // a chain of `entries` (`num_hashes` and mixed in hash) from `start_hash`
+    #[cfg(feature = "block-headers")]
+    fn entry_chain(start_hash: Hash, entries: &[(u64, Option<Hash>)]) -> Vec<EntryProof> { 
+        let mut prev_hash = start_hash;
+        entries.iter()
+            .map(|(num_hashes, transaction_hash)| { 
+                prev_hash = next_hash_with_tx_hash(&prev_hash, *num_hashes, *transaction_hash);
+                partial_entry(*num_hashes, prev_hash, *transaction_hash)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_next_hash_with_tx_hash() { 
+        let start_hash = Hash::new_unique();
+        let transaction_hash = Hash::new_unique();
+        assert_eq!(next_hash_with_tx_hash(&start_hash, 0, None), start_hash);
+        assert_eq!(next_hash_with_tx_hash(&start_hash, 1, None), hash(start_hash.as_ref()));
+        assert_eq!(next_hash_with_tx_hash(&start_hash, 3, None), hash(hash(hash(start_hash.as_ref()).as_ref()).as_ref()));
+        // the record is the last of the entry's hashes
+        assert_eq!(next_hash_with_tx_hash(&start_hash, 1, Some(transaction_hash)), hashv(&[start_hash.as_ref(), transaction_hash.as_ref()]));
+        assert_eq!(
+            next_hash_with_tx_hash(&start_hash, 2, Some(transaction_hash)),
+            hashv(&[hash(start_hash.as_ref()).as_ref(), transaction_hash.as_ref()])
+        );
+    }
+
+    #[cfg(feature = "block-headers")]
+    #[test]
+    fn test_entry_with_three_transactions() { 
+        let keypair = Keypair::new();
+        let leaves = [b"a", b"b", b"c"].map(|message| hash_leaf(keypair.sign_message(message).as_ref()));
+        // the merkle root, the odd leaf is paired with itself
+        let intermediate = |left: &Hash, right: &Hash| hashv(&[&[1], left.as_ref(), right.as_ref()]);
+        let root = intermediate(&intermediate(&leaves[0], &leaves[1]), &intermediate(&leaves[2], &leaves[2]));
+
+        let start_hash = Hash::new_unique();
+        let entries = entry_chain(start_hash, &[(5, None), (2, Some(root)), (5, None)]);
+        assert_eq!(verify_poh_ticks(start_hash, &entries), Ok(entries[2].hash()));
+        assert_eq!(PohEntry::from_entry(&entries[1]).map(|entry| entry.transaction_hash), Some(Some(root)));
+
+        // mixing in any single leaf instead of the root breaks the chain
+        let mut entries = entries;
+        entries[1] = partial_entry(2, entries[1].hash(), Some(leaves[0]));
+        assert!(matches!(verify_poh_ticks(start_hash, &entries), Err(PohError::EntryMismatch { index: 1, .. })));
+    }
+
+    #[cfg(feature = "block-headers")]
+    #[test]
+    fn test_verify_poh_ticks_rejects_corrupted_num_hashes() { 
+        let start_hash = Hash::new_unique();
+        let mut entries = entry_chain(start_hash, &[(5, None), (3, Some(Hash::new_unique())), (5, None)]);
+        assert!(verify_poh_ticks(start_hash, &entries).is_ok());
+
+        let entry = PohEntry::from_entry(&entries[1]).unwrap();
+        entries[1] = partial_entry(entry.num_hashes + 1, entry.hash, entry.transaction_hash);
+        assert!(matches!(
+            verify_poh_ticks(start_hash, &entries),
+            Err(PohError::EntryMismatch { index: 1, kind: EntryKind::Partial, .. })
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_next_hash_matches_solana_entry() { 
+        let keypair = Keypair::new();
+        let start_hash = Hash::new_unique();
+        let transactions: Vec<VersionedTransaction> = (1..=3)
+            .map(|lamports| system_transaction::transfer(&keypair, &Pubkey::new_unique(), lamports, start_hash).into())
+            .collect();
+        // a single transaction's merkle root is its leaf hash
+        assert_eq!(hash_transactions(&transactions[..1]), transaction_leaf_hash(&transactions[0].signatures[0]));
+
+        for num_hashes in [0, 1, 2, 3, 64, 12_500] { 
+            assert_eq!(next_hash_with_tx_hash(&start_hash, num_hashes, None), next_hash(&start_hash, num_hashes, &[]), "{} hashes", num_hashes);
+            for transactions in [&transactions[..1], &transactions[..]] { 
+                let transaction_hash = hash_transactions(transactions);
+                let got = next_hash_with_tx_hash(&start_hash, num_hashes, Some(transaction_hash));
+                assert_eq!(got, next_hash(&start_hash, num_hashes, transactions), "{} hashes, {} txs", num_hashes, transactions.len());
+            }
+        }
+
+        // solana-entry accepts an entry hashed here
+        let hash = next_hash_with_tx_hash(&start_hash, 12, Some(hash_transactions(&transactions)));
+        assert!(Entry { num_hashes: 12, hash, transactions }.verify(&start_hash));
+    }
+}
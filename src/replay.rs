@@ -0,0 +1,154 @@
+use std::{future::Future, path::{Path, PathBuf}};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use solana_client::rpc_response::{RpcVersionInfo, RpcVoteAccountStatus};
+use solana_sdk::{commitment_config::CommitmentConfig, epoch_info::EpochInfo, epoch_schedule::EpochSchedule, hash::hash, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+use tracing::{debug, warn};
+
+use crate::error::LightNodeError;
+use crate::transport::{parse_rpc_result, HttpTransport, RpcTransport};
+
+/// serves rpc responses saved in a directory instead of a node, so a 
+/// verification can be rerun offline (eg. to reproduce a failure from a bug 
+/// report). each response is a json file named after the method and a hash 
+/// of its params, saved by `ReplayTransport::recording`. a response which 
+/// wasnt saved fails with `LightNodeError::ReplayMissing`
+pub struct ReplayTransport<T = HttpTransport> { 
+    dir: PathBuf,
+    // when recording, the transport the responses are fetched with
+    upstream: Option<T>,
+}
+
+impl ReplayTransport { 
+    /// replay the responses saved in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self { 
+        Self { dir: dir.into(), upstream: None }
+    }
+}
+
+impl<T: RpcTransport> ReplayTransport<T> { 
+    /// fetch responses with `upstream`, saving each one to `dir` for 
+    /// `ReplayTransport::new` to replay later
+    pub fn recording(dir: impl Into<PathBuf>, upstream: T) -> Self { 
+        Self { dir: dir.into(), upstream: Some(upstream) }
+    }
+
+    pub fn dir(&self) -> &Path { 
+        &self.dir
+    }
+
+    pub fn is_recording(&self) -> bool { 
+        self.upstream.is_some()
+    }
+
+    fn path(&self, method: &str, params: &serde_json::Value) -> PathBuf { 
+        let params_hash = hash(params.to_string().as_bytes());
+        self.dir.join(format!("{}-{}.json", method, params_hash))
+    }
+
+    async fn read(&self, method: &str, path: &Path) -> Result<String, LightNodeError> { 
+        match tokio::fs::read_to_string(path).await { 
+            Ok(resp) => { 
+                debug!(method, path = %path.display(), "replayed response");
+                Ok(resp)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => { 
+                Err(LightNodeError::ReplayMissing { method: method.to_string(), path: path.to_path_buf() })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // a failed write only leaves the recording incomplete
+    async fn write(&self, method: &str, path: &Path, resp: &str) { 
+        let result = async { 
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(path, resp).await
+        }.await;
+        if let Err(e) = result { 
+            warn!(method, path = %path.display(), "failed to record response: {}", e);
+        }
+    }
+
+    // a raw json response, as the light client parses it
+    async fn raw<'a, F, Fut>(&'a self, method: &str, params: serde_json::Value, fetch: F) -> Result<String, LightNodeError> 
+    where 
+        F: FnOnce(&'a T) -> Fut,
+        Fut: Future<Output = Result<String, LightNodeError>>,
+    { 
+        let path = self.path(method, &params);
+        match &self.upstream { 
+            Some(upstream) => { 
+                let resp = fetch(upstream).await?;
+                self.write(method, &path, &resp).await;
+                Ok(resp)
+            }
+            None => self.read(method, &path).await,
+        }
+    }
+
+    // a result the transport parses, saved as a json-rpc response holding it
+    async fn result<'a, R, F, Fut>(&'a self, method: &str, params: serde_json::Value, fetch: F) -> Result<R, LightNodeError> 
+    where 
+        R: Serialize + DeserializeOwned,
+        F: FnOnce(&'a T) -> Fut,
+        Fut: Future<Output = Result<R, LightNodeError>>,
+    { 
+        let path = self.path(method, &params);
+        match &self.upstream { 
+            Some(upstream) => { 
+                let result = fetch(upstream).await?;
+                let resp = serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 0 });
+                self.write(method, &path, &resp.to_string()).await;
+                Ok(result)
+            }
+            None => parse_rpc_result(&self.read(method, &path).await?),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: RpcTransport> RpcTransport for ReplayTransport<T> { 
+    async fn get_block(&self, slot: u64, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        let params = serde_json::json!([slot, config]);
+        self.raw("getBlock", params, |x| x.get_block(slot, config.clone())).await
+    }
+
+    async fn get_transaction(&self, signature: Signature, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        let params = serde_json::json!([signature.to_string(), config]);
+        self.raw("getTransaction", params, |x| x.get_transaction(signature, config.clone())).await
+    }
+
+    async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<String, LightNodeError> { 
+        let params = serde_json::json!([slot, signature.to_string()]);
+        self.raw("getBlockHeaders", params, |x| x.get_block_headers(slot, signature)).await
+    }
+
+    async fn get_epoch_info(&self, commitment: CommitmentConfig) -> Result<EpochInfo, LightNodeError> { 
+        self.result("getEpochInfo", serde_json::json!([commitment]), |x| x.get_epoch_info(commitment)).await
+    }
+
+    async fn get_vote_accounts(&self, commitment: CommitmentConfig) -> Result<RpcVoteAccountStatus, LightNodeError> { 
+        self.result("getVoteAccounts", serde_json::json!([commitment]), |x| x.get_vote_accounts(commitment)).await
+    }
+
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule, LightNodeError> { 
+        self.result("getEpochSchedule", serde_json::json!([]), |x| x.get_epoch_schedule()).await
+    }
+
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64, commitment: CommitmentConfig) -> Result<Vec<u64>, LightNodeError> { 
+        let params = serde_json::json!([start_slot, end_slot, commitment]);
+        self.result("getBlocks", params, |x| x.get_blocks(start_slot, end_slot, commitment)).await
+    }
+
+    async fn get_version(&self) -> Result<RpcVersionInfo, LightNodeError> { 
+        self.result("getVersion", serde_json::json!([]), |x| x.get_version()).await
+    }
+
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>, LightNodeError> { 
+        let params = serde_json::json!([signatures.iter().map(|x| x.to_string()).collect::<Vec<_>>()]);
+        self.result("getSignatureStatuses", params, |x| x.get_signature_statuses(signatures)).await
+    }
+}
@@ -0,0 +1,117 @@
+use std::{future::Future, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use tracing::debug;
+
+use crate::error::LightNodeError;
+
+/// how rpc calls are retried on transient failures (see 
+/// `LightNodeError::is_transient`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy { 
+    /// attempts including the first one
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// backoff growth per attempt
+    pub multiplier: f64,
+    /// how long a single `getBlock` or `getBlockHeaders` attempt may take before it fails with 
+    /// `LightNodeError::Timeout` (and is retried), `None` waits for the 
+    /// transport's own request timeout
+    pub attempt_timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy { 
+    fn default() -> Self { 
+        Self { 
+            max_attempts: 30,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 1.5,
+            attempt_timeout: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+impl RetryPolicy { 
+    /// never retry
+    pub fn none() -> Self { 
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// backoff before the attempt after `attempt` (starting at 1), with 
+    /// jitter so concurrent requests dont retry in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration { 
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        // clamped so a negative or non-finite backoff cant panic `from_secs_f64`
+        let backoff = backoff.max(0.0).min(self.max_backoff.as_secs_f64());
+        // between half and the full backoff
+        Duration::from_secs_f64(backoff * (0.5 + 0.5 * jitter()))
+    }
+
+    pub fn with_attempt_timeout(mut self, attempt_timeout: Option<Duration>) -> Self { 
+        self.attempt_timeout = attempt_timeout;
+        self
+    }
+
+    /// run a single attempt of `method`, bounded by `attempt_timeout`
+    pub async fn attempt<Fut, T>(&self, method: &str, attempt: Fut) -> Result<T, LightNodeError> 
+    where 
+        Fut: Future<Output = Result<T, LightNodeError>>,
+    { 
+        match self.attempt_timeout { 
+            Some(timeout) => tokio::time::timeout(timeout, attempt)
+                .await
+                .map_err(|_| LightNodeError::Timeout { method: method.to_string(), timeout })?,
+            None => attempt.await,
+        }
+    }
+}
+
+// a number in [0, 1). only spreads retries out, so doesnt need a proper rng
+fn jitter() -> f64 { 
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+    f64::from(nanos % 1_000) / 1_000.0
+}
+
+/// run `f` until it succeeds, fails with a permanent error or runs out of 
+/// attempts, returning the last error
+pub async fn retry<F, Fut, T>(policy: &RetryPolicy, mut f: F) -> Result<T, LightNodeError> 
+where 
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, LightNodeError>>,
+{ 
+    let mut attempt = 0;
+    loop { 
+        attempt += 1;
+        match f().await { 
+            Ok(value) => return Ok(value),
+            Err(e) if !e.is_transient() || attempt >= policy.max_attempts => return Err(e),
+            Err(e) => { 
+                let mut backoff = policy.backoff(attempt);
+                // wait at least as long as the endpoint asked
+                if let LightNodeError::RateLimited { retry_after: Some(retry_after), .. } = e { 
+                    backoff = backoff.max(retry_after);
+                }
+                debug!(attempt, ?backoff, "retrying: {}", e);
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_clamped() { 
+        let max_backoff = RetryPolicy::default().max_backoff;
+        for multiplier in [f64::INFINITY, f64::NAN, -3.0, 1e300] { 
+            let policy = RetryPolicy { multiplier, ..RetryPolicy::default() };
+            for attempt in [1, 2, 1_000, u32::MAX] { 
+                assert!(policy.backoff(attempt) <= max_backoff);
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use serde::Serialize;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+
+use crate::client::LightClient;
+use crate::error::LightNodeError;
+use crate::transport::RpcTransport;
+use crate::verify::{deserialize_block_header, verify_header_bank_hash};
+use crate::version::ClusterVersion;
+
+// finalized slots searched back from the finalized slot for a block
+const SLOTS_SEARCHED: u64 = 64;
+
+/// outcome of a self test check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check { 
+    Passed,
+    Failed(String),
+    /// not run as a check it depends on failed
+    Skipped,
+}
+
+impl Check { 
+    pub fn passed(&self) -> bool { 
+        matches!(self, Check::Passed)
+    }
+}
+
+/// which of the capabilities verification depends on the endpoint has, 
+/// see `LightClient::self_test`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelfTestReport { 
+    /// `solana-core` version from `getVersion`
+    pub cluster_version: Option<String>,
+    /// the cluster version is in the supported range
+    pub version: Check,
+    /// the recent finalized slot the other checks ran on
+    pub slot: Option<u64>,
+    /// `getBlockHeaders` answered with headers which deserialize
+    pub block_headers: Check,
+    /// the header's entries form a PoH chain
+    pub poh: Check,
+    /// votes on the slot were found in the following blocks
+    pub vote_scan: Check,
+    /// the votes are for the bank hash recomputed with the client's 
+    /// `BankHashScheme`
+    pub bank_hash: Check,
+}
+
+impl SelfTestReport { 
+    /// every check passed, so verification should work against the endpoint
+    pub fn passed(&self) -> bool { 
+        [&self.version, &self.block_headers, &self.poh, &self.vote_scan, &self.bank_hash]
+            .iter()
+            .all(|check| check.passed())
+    }
+}
+
+impl<T: RpcTransport> LightClient<T> { 
+    /// probe the endpoint for what verification needs: a supported cluster 
+    /// version, `getBlockHeaders` for a recent finalized slot, a PoH chain 
+    /// which verifies, and votes for the recomputed bank hash. rpc failures 
+    /// are reported as failed checks rather than errors
+    pub async fn self_test(&self) -> SelfTestReport { 
+        let mut report = SelfTestReport { 
+            cluster_version: None,
+            version: Check::Skipped,
+            slot: None,
+            block_headers: Check::Skipped,
+            poh: Check::Skipped,
+            vote_scan: Check::Skipped,
+            bank_hash: Check::Skipped,
+        };
+
+        report.version = match self.cluster_version().await { 
+            Ok(version) => { 
+                report.cluster_version = Some(version.to_string());
+                match version.parse::<ClusterVersion>() { 
+                    Ok(x) if x.is_supported() => Check::Passed,
+                    Ok(_) => Check::Failed(format!("{} is outside the supported range", version)),
+                    Err(e) => Check::Failed(e.to_string()),
+                }
+            }
+            Err(e) => Check::Failed(e.to_string()),
+        };
+
+        let (slot, signature) = match self.recent_block().await { 
+            Ok(block) => block,
+            Err(e) => { 
+                report.block_headers = Check::Failed(format!("no recent block to test with: {}", e));
+                return report;
+            }
+        };
+        report.slot = Some(slot);
+
+        let block_headers = self.get_block_headers(slot, signature)
+            .await
+            .and_then(|resp| deserialize_block_header(&resp.result));
+        let block_headers = match block_headers { 
+            Ok(block_headers) => block_headers,
+            Err(e) => { 
+                report.block_headers = Check::Failed(e.to_string());
+                return report;
+            }
+        };
+        report.block_headers = Check::Passed;
+
+        let bank_hash = match verify_header_bank_hash(&block_headers, self.epoch_accounts_hash(slot), &signature, self.bank_hash_scheme()) { 
+            Ok((_, bank_hash)) => bank_hash,
+            Err(e) => { 
+                report.poh = Check::Failed(e.to_string());
+                return report;
+            }
+        };
+        report.poh = Check::Passed;
+
+        let finality = match self.verify_finality(slot, bank_hash).await { 
+            Ok(finality) => finality,
+            Err(e) => { 
+                report.vote_scan = Check::Failed(e.to_string());
+                return report;
+            }
+        };
+        if finality.collected_stake == 0 { 
+            report.vote_scan = Check::Failed(format!("no votes on slot {} in {} slots", slot, finality.slots_scanned));
+            return report;
+        }
+        report.vote_scan = Check::Passed;
+
+        report.bank_hash = if finality.voted_stake > 0 { 
+            Check::Passed
+        } else { 
+            Check::Failed(format!(
+                "votes are for another bank hash than {}, the bank hash scheme may not match the cluster's",
+                bank_hash
+            ))
+        };
+        report
+    }
+
+    // the newest recent finalized block and its first transaction's signature, 
+    // which its block headers are requested with
+    async fn recent_block(&self) -> Result<(u64, Signature), LightNodeError> { 
+        let finalized = CommitmentConfig::finalized();
+        let finalized_slot = self.rpc_client().get_slot_with_commitment(finalized).await?;
+        let start_slot = finalized_slot.saturating_sub(SLOTS_SEARCHED);
+        let slots = self.rpc_client().get_blocks_with_commitment(start_slot, Some(finalized_slot), finalized).await?;
+        for slot in slots.into_iter().rev() { 
+            if let Some(signature) = self.get_block_signatures(slot).await?.first() { 
+                return Ok((slot, *signature));
+            }
+        }
+        Err(LightNodeError::BlockMissing { slot: finalized_slot })
+    }
+}
@@ -0,0 +1,519 @@
+use std::{future::Future, sync::{atomic::{AtomicU64, Ordering}, Mutex}, time::{Duration, Instant}};
+
+use async_trait::async_trait;
+use reqwest::{header::{HeaderMap, RETRY_AFTER}, StatusCode};
+use serde::de::DeserializeOwned;
+use solana_client::rpc_response::{Response as RpcResponse, RpcVersionInfo, RpcVoteAccountStatus};
+use solana_sdk::{commitment_config::CommitmentConfig, epoch_info::EpochInfo, epoch_schedule::EpochSchedule, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+use tokio::sync::Semaphore;
+
+use tracing::{debug, warn};
+
+use crate::client::JsonRpcResponse;
+use crate::error::LightNodeError;
+
+/// the rpc methods verification depends on, so the light client can be 
+/// driven by something other than a live node (eg. recorded fixtures). 
+/// `getBlock`, `getTransaction` and `getBlockHeaders` return the raw json 
+/// response, which the light client parses
+#[async_trait]
+pub trait RpcTransport: Send + Sync { 
+    async fn get_block(&self, slot: u64, config: serde_json::Value) -> Result<String, LightNodeError>;
+    async fn get_transaction(&self, signature: Signature, config: serde_json::Value) -> Result<String, LightNodeError>;
+    async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<String, LightNodeError>;
+    async fn get_epoch_info(&self, commitment: CommitmentConfig) -> Result<EpochInfo, LightNodeError>;
+    async fn get_vote_accounts(&self, commitment: CommitmentConfig) -> Result<RpcVoteAccountStatus, LightNodeError>;
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule, LightNodeError>;
+    /// slots with a block between `start_slot` and `end_slot` (inclusive)
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64, commitment: CommitmentConfig) -> Result<Vec<u64>, LightNodeError>;
+    async fn get_version(&self) -> Result<RpcVersionInfo, LightNodeError>;
+    /// statuses of `signatures`, searching the ledger history too. `None` for 
+    /// the signatures the node has never seen
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>, LightNodeError>;
+}
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// requests in flight to an endpoint at once
+pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+
+/// json-rpc over http
+pub struct HttpTransport { 
+    endpoint: String,
+    http_client: reqwest::Client,
+    timeout: Duration,
+    connect_timeout: Duration,
+    max_connections: usize,
+    // caps the requests in flight, eg. from `verify_many` and the vote scan
+    in_flight: Semaphore,
+    // set from `Retry-After` when the endpoint rate limits a request, so the 
+    // other requests back off too
+    rate_limited_until: Mutex<Option<Instant>>,
+    // each request gets its own id, for proxies which multiplex on it
+    next_request_id: AtomicU64,
+}
+
+impl HttpTransport { 
+    /// `timeout` bounds each request, including reading the response, so an 
+    /// endpoint which accepts the connection but never responds fails with 
+    /// `LightNodeError::Timeout`
+    pub fn new(endpoint: impl Into<String>, timeout: Duration) -> Self { 
+        Self { 
+            endpoint: endpoint.into(),
+            http_client: build_http_client(timeout, DEFAULT_CONNECT_TIMEOUT, DEFAULT_MAX_CONNECTIONS),
+            timeout,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            in_flight: Semaphore::new(DEFAULT_MAX_CONNECTIONS),
+            rate_limited_until: Mutex::new(None),
+            next_request_id: AtomicU64::new(1),
+        }
+    }
+
+    /// id of the next request, incremented for each request after it. 
+    /// starts at 1
+    pub fn with_first_request_id(self, request_id: u64) -> Self { 
+        self.next_request_id.store(request_id, Ordering::Relaxed);
+        self
+    }
+
+    /// how long to wait for a connection to the endpoint
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self { 
+        self.connect_timeout = connect_timeout;
+        self.http_client = build_http_client(self.timeout, connect_timeout, self.max_connections);
+        self
+    }
+
+    /// max requests in flight to the endpoint at once, the rest wait for one 
+    /// to finish. public rpcs rate limit (http 429) bursts of requests
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self { 
+        self.max_connections = max_connections.max(1);
+        self.http_client = build_http_client(self.timeout, self.connect_timeout, self.max_connections);
+        self.in_flight = Semaphore::new(self.max_connections);
+        self
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<String, LightNodeError> { 
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.next_request_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        }).to_string();
+
+        let rate_limited_until = *self.rate_limited_until.lock().unwrap();
+        if let Some(rate_limited_until) = rate_limited_until { 
+            tokio::time::sleep_until(rate_limited_until.into()).await;
+        }
+        // the semaphore is never closed
+        let _permit = self.in_flight.acquire().await;
+
+        let map_err = |e: reqwest::Error| { 
+            if !e.is_timeout() { 
+                return LightNodeError::Transport(e);
+            }
+            let timeout = if e.is_connect() { self.connect_timeout } else { self.timeout };
+            LightNodeError::Timeout { method: method.to_string(), timeout }
+        };
+        let res = send_rpc_call(&self.http_client, &self.endpoint, request).await.map_err(map_err)?;
+        if res.status() == StatusCode::TOO_MANY_REQUESTS { 
+            let retry_after = retry_after(res.headers());
+            warn!(endpoint = %self.endpoint, method, ?retry_after, "rate limited");
+            if let Some(retry_after) = retry_after { 
+                *self.rate_limited_until.lock().unwrap() = Some(Instant::now() + retry_after);
+            }
+            return Err(LightNodeError::RateLimited { method: method.to_string(), retry_after });
+        }
+        res.text().await.map_err(map_err)
+    }
+
+    // for methods whose result is parsed here rather than by the light client
+    async fn call_for_result<T: DeserializeOwned>(&self, method: &str, params: serde_json::Value) -> Result<T, LightNodeError> { 
+        parse_rpc_result(&self.call(method, params).await?)
+    }
+}
+
+/// the result of a json-rpc response, or its error
+pub(crate) fn parse_rpc_result<T: DeserializeOwned>(resp: &str) -> Result<T, LightNodeError> { 
+    serde_json::from_str::<JsonRpcResponse<T>>(resp)?.into_result()
+}
+
+#[async_trait]
+impl RpcTransport for HttpTransport { 
+    async fn get_block(&self, slot: u64, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.call("getBlock", serde_json::json!([slot, config])).await
+    }
+
+    async fn get_transaction(&self, signature: Signature, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.call("getTransaction", serde_json::json!([signature.to_string(), config])).await
+    }
+
+    async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<String, LightNodeError> { 
+        self.call("getBlockHeaders", serde_json::json!([slot, signature.as_ref()])).await
+    }
+
+    async fn get_epoch_info(&self, commitment: CommitmentConfig) -> Result<EpochInfo, LightNodeError> { 
+        self.call_for_result("getEpochInfo", serde_json::json!([commitment])).await
+    }
+
+    async fn get_vote_accounts(&self, commitment: CommitmentConfig) -> Result<RpcVoteAccountStatus, LightNodeError> { 
+        self.call_for_result("getVoteAccounts", serde_json::json!([commitment])).await
+    }
+
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule, LightNodeError> { 
+        self.call_for_result("getEpochSchedule", serde_json::json!([])).await
+    }
+
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64, commitment: CommitmentConfig) -> Result<Vec<u64>, LightNodeError> { 
+        self.call_for_result("getBlocks", serde_json::json!([start_slot, end_slot, commitment])).await
+    }
+
+    async fn get_version(&self) -> Result<RpcVersionInfo, LightNodeError> { 
+        self.call_for_result("getVersion", serde_json::json!([])).await
+    }
+
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>, LightNodeError> { 
+        let signatures = signatures.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let config = serde_json::json!({ "searchTransactionHistory": true });
+        let resp: RpcResponse<Vec<Option<TransactionStatus>>> = self.call_for_result("getSignatureStatuses", serde_json::json!([signatures, config])).await?;
+        Ok(resp.value)
+    }
+}
+
+fn build_http_client(timeout: Duration, connect_timeout: Duration, max_connections: usize) -> reqwest::Client { 
+    // keep idle connections around so consecutive block requests reuse them. 
+    // large getBlock responses are much smaller compressed, so advertise 
+    // gzip and deflate (decompressed transparently)
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .connect_timeout(connect_timeout)
+        .gzip(true)
+        .deflate(true)
+        .tcp_keepalive(Some(Duration::from_secs(60)))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(max_connections)
+        .build()
+        .expect("failed to build http client")
+}
+
+async fn send_rpc_call(client: &reqwest::Client, url: &str, body: String) -> Result<reqwest::Response, reqwest::Error> { 
+    use reqwest::header::{ACCEPT, CONTENT_TYPE};
+
+    client
+        .post(url)
+        .body(body)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT, "application/json")
+        .send()
+        .await
+}
+
+// `Retry-After` in seconds. the http date form isnt used by rpc providers
+fn retry_after(headers: &HeaderMap) -> Option<Duration> { 
+    let retry_after = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    retry_after.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+// failures in a row before an endpoint is skipped for a while
+const UNHEALTHY_AFTER_FAILURES: u32 = 3;
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// an endpoint of a `FailoverTransport`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcEndpoint { 
+    pub url: String,
+    /// whether the endpoint serves the light node's `getBlockHeaders` method
+    pub block_headers: bool,
+}
+
+impl RpcEndpoint { 
+    pub fn new(url: impl Into<String>) -> Self { 
+        Self { url: url.into(), block_headers: true }
+    }
+
+    /// only use the endpoint for the standard rpc methods, eg. a public rpc
+    pub fn without_block_headers(mut self) -> Self { 
+        self.block_headers = false;
+        self
+    }
+}
+
+impl From<String> for RpcEndpoint { 
+    fn from(url: String) -> Self { 
+        RpcEndpoint::new(url)
+    }
+}
+
+impl From<&str> for RpcEndpoint { 
+    fn from(url: &str) -> Self { 
+        RpcEndpoint::new(url)
+    }
+}
+
+#[derive(Default)]
+struct EndpointHealth { 
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+struct FailoverEndpoint { 
+    endpoint: RpcEndpoint,
+    transport: HttpTransport,
+    health: Mutex<EndpointHealth>,
+}
+
+impl FailoverEndpoint { 
+    fn is_healthy(&self) -> bool { 
+        match self.health.lock().unwrap().unhealthy_until { 
+            Some(unhealthy_until) => Instant::now() >= unhealthy_until,
+            None => true,
+        }
+    }
+
+    fn record(&self, success: bool) { 
+        let mut health = self.health.lock().unwrap();
+        if success { 
+            *health = EndpointHealth::default();
+            return;
+        }
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= UNHEALTHY_AFTER_FAILURES { 
+            warn!(url = %self.endpoint.url, "marking endpoint unhealthy for {:?}", UNHEALTHY_COOLDOWN);
+            health.unhealthy_until = Some(Instant::now() + UNHEALTHY_COOLDOWN);
+        }
+    }
+}
+
+/// json-rpc over http to several endpoints in priority order: a request 
+/// fails over to the next endpoint on a transient error, and endpoints 
+/// which keep failing are skipped until their cooldown passes
+pub struct FailoverTransport { 
+    endpoints: Vec<FailoverEndpoint>,
+}
+
+impl FailoverTransport { 
+    pub fn new(endpoints: impl IntoIterator<Item = impl Into<RpcEndpoint>>, timeout: Duration) -> Self { 
+        let endpoints = endpoints
+            .into_iter()
+            .map(|endpoint| { 
+                let endpoint = endpoint.into();
+                FailoverEndpoint { 
+                    transport: HttpTransport::new(endpoint.url.clone(), timeout),
+                    endpoint,
+                    health: Mutex::new(EndpointHealth::default()),
+                }
+            })
+            .collect();
+        Self { endpoints }
+    }
+
+    /// how long to wait for a connection to each endpoint
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self { 
+        self.endpoints = self.endpoints
+            .into_iter()
+            .map(|x| FailoverEndpoint { 
+                transport: x.transport.with_connect_timeout(connect_timeout),
+                ..x
+            })
+            .collect();
+        self
+    }
+
+    /// max requests in flight to each endpoint at once
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self { 
+        self.endpoints = self.endpoints
+            .into_iter()
+            .map(|x| FailoverEndpoint { 
+                transport: x.transport.with_max_connections(max_connections),
+                ..x
+            })
+            .collect();
+        self
+    }
+
+    pub fn endpoints(&self) -> impl Iterator<Item = &RpcEndpoint> { 
+        self.endpoints.iter().map(|x| &x.endpoint)
+    }
+
+    async fn call<'a, F, Fut, T>(&'a self, block_headers: bool, f: F) -> Result<T, LightNodeError> 
+    where 
+        F: Fn(&'a HttpTransport) -> Fut,
+        Fut: Future<Output = Result<T, LightNodeError>>,
+    { 
+        let candidates = self.endpoints
+            .iter()
+            .filter(|x| !block_headers || x.endpoint.block_headers)
+            .collect::<Vec<_>>();
+        // when every endpoint is unhealthy, try them all anyway
+        let healthy = candidates.iter().copied().filter(|x| x.is_healthy()).collect::<Vec<_>>();
+        let candidates = if healthy.is_empty() { candidates } else { healthy };
+
+        let mut last_error = None;
+        for candidate in candidates { 
+            match f(&candidate.transport).await { 
+                Ok(resp) => { 
+                    candidate.record(true);
+                    return Ok(resp);
+                }
+                Err(e) if e.is_transient() => { 
+                    debug!(url = %candidate.endpoint.url, "failing over: {}", e);
+                    candidate.record(false);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or(LightNodeError::NoEndpoints { block_headers }))
+    }
+}
+
+#[async_trait]
+impl RpcTransport for FailoverTransport { 
+    async fn get_block(&self, slot: u64, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.call(false, |x| x.get_block(slot, config.clone())).await
+    }
+
+    async fn get_transaction(&self, signature: Signature, config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.call(false, |x| x.get_transaction(signature, config.clone())).await
+    }
+
+    async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<String, LightNodeError> { 
+        self.call(true, |x| x.get_block_headers(slot, signature)).await
+    }
+
+    async fn get_epoch_info(&self, commitment: CommitmentConfig) -> Result<EpochInfo, LightNodeError> { 
+        self.call(false, |x| x.get_epoch_info(commitment)).await
+    }
+
+    async fn get_vote_accounts(&self, commitment: CommitmentConfig) -> Result<RpcVoteAccountStatus, LightNodeError> { 
+        self.call(false, |x| x.get_vote_accounts(commitment)).await
+    }
+
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule, LightNodeError> { 
+        self.call(false, |x| x.get_epoch_schedule()).await
+    }
+
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64, commitment: CommitmentConfig) -> Result<Vec<u64>, LightNodeError> { 
+        self.call(false, |x| x.get_blocks(start_slot, end_slot, commitment)).await
+    }
+
+    // the version of the endpoints the block headers come from
+    async fn get_version(&self) -> Result<RpcVersionInfo, LightNodeError> { 
+        self.call(true, |x| x.get_version()).await
+    }
+
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>, LightNodeError> { 
+        self.call(false, |x| x.get_signature_statuses(signatures)).await
+    }
+}
+
+/// responses served from memory for tests, keyed by method and its main 
+/// param (the slot or signature, empty for the others). each call takes the 
+/// next queued response and the last one repeats, so a transient error 
+/// followed by a result exercises the retries
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryTransport { 
+    responses: Mutex<std::collections::HashMap<(&'static str, String), std::collections::VecDeque<String>>>,
+    calls: Mutex<std::collections::HashMap<&'static str, usize>>,
+}
+
+#[cfg(test)]
+impl MemoryTransport { 
+    pub(crate) fn with_response(self, method: &'static str, key: impl ToString, body: impl Into<String>) -> Self { 
+        self.responses.lock().unwrap().entry((method, key.to_string())).or_default().push_back(body.into());
+        self
+    }
+
+    pub(crate) fn with_result(self, method: &'static str, key: impl ToString, result: impl serde::Serialize) -> Self { 
+        let body = serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }).to_string();
+        self.with_response(method, key, body)
+    }
+
+    pub(crate) fn with_error(self, method: &'static str, key: impl ToString, code: i64, message: &str) -> Self { 
+        let body = serde_json::json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": 1 }).to_string();
+        self.with_response(method, key, body)
+    }
+
+    /// how many times `method` was requested
+    pub(crate) fn calls(&self, method: &str) -> usize { 
+        self.calls.lock().unwrap().get(method).copied().unwrap_or_default()
+    }
+
+    fn respond(&self, method: &'static str, key: impl ToString) -> Result<String, LightNodeError> { 
+        *self.calls.lock().unwrap().entry(method).or_default() += 1;
+        let key = key.to_string();
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses.get_mut(&(method, key.clone())).ok_or_else(|| LightNodeError::JsonRpc { 
+            code: -32601,
+            message: format!("no {} response for '{}'", method, key),
+        })?;
+        if queue.len() > 1 { 
+            Ok(queue.pop_front().unwrap())
+        } else { 
+            Ok(queue[0].clone())
+        }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl RpcTransport for MemoryTransport { 
+    async fn get_block(&self, slot: u64, _config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.respond("getBlock", slot)
+    }
+
+    async fn get_transaction(&self, signature: Signature, _config: serde_json::Value) -> Result<String, LightNodeError> { 
+        self.respond("getTransaction", signature)
+    }
+
+    async fn get_block_headers(&self, slot: u64, _signature: Signature) -> Result<String, LightNodeError> { 
+        self.respond("getBlockHeaders", slot)
+    }
+
+    async fn get_epoch_info(&self, _commitment: CommitmentConfig) -> Result<EpochInfo, LightNodeError> { 
+        parse_rpc_result(&self.respond("getEpochInfo", "")?)
+    }
+
+    async fn get_vote_accounts(&self, _commitment: CommitmentConfig) -> Result<RpcVoteAccountStatus, LightNodeError> { 
+        parse_rpc_result(&self.respond("getVoteAccounts", "")?)
+    }
+
+    async fn get_epoch_schedule(&self) -> Result<EpochSchedule, LightNodeError> { 
+        parse_rpc_result(&self.respond("getEpochSchedule", "")?)
+    }
+
+    // the slots queued for "getBlocks" which are in the range
+    async fn get_blocks(&self, start_slot: u64, end_slot: u64, _commitment: CommitmentConfig) -> Result<Vec<u64>, LightNodeError> { 
+        let slots: Vec<u64> = parse_rpc_result(&self.respond("getBlocks", "")?)?;
+        Ok(slots.into_iter().filter(|slot| (start_slot..=end_slot).contains(slot)).collect())
+    }
+
+    async fn get_version(&self) -> Result<RpcVersionInfo, LightNodeError> { 
+        parse_rpc_result(&self.respond("getVersion", "")?)
+    }
+
+    async fn get_signature_statuses(&self, _signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>, LightNodeError> { 
+        let resp: RpcResponse<Vec<Option<TransactionStatus>>> = parse_rpc_result(&self.respond("getSignatureStatuses", "")?)?;
+        Ok(resp.value)
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unresponsive_endpoint_times_out() { 
+        // connections are queued by the os but never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let timeout = Duration::from_millis(200);
+        let transport = HttpTransport::new(endpoint, timeout);
+
+        let err = transport.get_block(5, serde_json::json!({})).await.unwrap_err();
+        assert!(matches!(&err, LightNodeError::Timeout { method, timeout: t } if method == "getBlock" && *t == timeout), "{}", err);
+        assert!(err.is_transient());
+    }
+}
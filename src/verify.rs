@@ -0,0 +1,1063 @@
+use std::{fmt::{self, Write}, time::Duration};
+#[cfg(feature = "native")]
+use std::{collections::HashSet, sync::Mutex, time::Instant};
+
+#[cfg(feature = "native")]
+use futures::{stream, StreamExt};
+use serde::{Serialize, Serializer};
+use solana_sdk::{commitment_config::CommitmentLevel, hash::Hash, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{BlockHeader, EntryProof};
+use tracing::{debug, warn};
+#[cfg(feature = "native")]
+use solana_sdk::{commitment_config::CommitmentConfig, instruction::Instruction, signer::signers::Signers, transaction::{Transaction, TransactionError}};
+#[cfg(feature = "native")]
+use tracing::instrument;
+
+#[cfg(feature = "native")]
+use crate::client::LightClient;
+use crate::bank_hash::{BankHashInputs, BankHashScheme, EpochAccountsHashBankHash};
+use crate::error::LightNodeError;
+use crate::merkle::verify_merkle_inclusion;
+use crate::poh::{verify_entry_chain, EntryKind, PohEntry, PohError};
+#[cfg(feature = "native")]
+use crate::transport::RpcTransport;
+use crate::votes::{FinalityConfidence, SupermajorityThreshold};
+#[cfg(feature = "native")]
+use crate::votes::{stake_ratio, VoteWindow};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VerificationReport { 
+    pub slot: u64,
+    /// epoch of `slot`
+    pub slot_epoch: u64,
+    /// epoch the stakes were fetched in, newer than `slot_epoch` when 
+    /// verifying a slot from a past epoch (the stakes are then approximate)
+    pub stake_epoch: u64,
+    /// the transaction whose inclusion was verified, `None` when only the 
+    /// block itself was verified
+    #[serde(serialize_with = "serialize_option_base58")]
+    pub signature: Option<Signature>,
+    /// the signature's merkle proof verified against its entry
+    pub tx_included: bool,
+    /// the entries form a valid PoH chain from the start blockhash
+    pub poh_verified: bool,
+    /// the last entry hash of the verified PoH chain
+    #[serde(serialize_with = "serialize_base58")]
+    pub blockhash: Hash,
+    /// the bank hash recomputed from the block headers
+    #[serde(serialize_with = "serialize_base58")]
+    pub bank_hash: Hash,
+    /// stake which voted for `bank_hash`
+    pub voted_stake: u128,
+    /// current and delinquent stake
+    pub total_stake: u128,
+    /// stake of the validators which are currently voting
+    pub current_total_stake: u128,
+    pub delinquent_stake: u128,
+    /// the stake `is_supermajority` was checked against, either 
+    /// `total_stake` or `current_total_stake` (see `SupermajorityStake`)
+    pub threshold_stake: u128,
+    /// `voted_stake / threshold_stake`, for display. rounded, 
+    /// `is_supermajority` is the exact check
+    pub voted_stake_ratio: f64,
+    /// the share of `threshold_stake` `is_supermajority` was checked for
+    #[serde(serialize_with = "serialize_display")]
+    pub supermajority_threshold: SupermajorityThreshold,
+    /// votes whose stake couldnt be found, so arent in `voted_stake`
+    pub unattributed_votes: usize,
+    /// vote accounts whose vote's signatures didnt verify, see 
+    /// `VoteTally::signature_verification_failures`
+    #[serde(serialize_with = "serialize_base58_seq")]
+    pub signature_verification_failures: Vec<Pubkey>,
+    /// slots without a block in the window scanned for votes
+    pub skipped_slots: Vec<u64>,
+    /// slots in the window whose block wasnt available yet, see 
+    /// `VoteTally::unavailable_slots`
+    pub unavailable_slots: Vec<u64>,
+    /// number of slots scanned for votes
+    pub slots_scanned: u64,
+    /// stake which voted on the slot, for any bank hash
+    pub collected_stake: u128,
+    /// `solana-core` version of the cluster, when it was checked
+    pub cluster_version: Option<String>,
+    /// commitment the slot had reached when its votes were checked
+    pub commitment: Option<CommitmentLevel>,
+    /// bank hashes with significant stake when the votes are split, see 
+    /// `VoteTally::competing_bank_hashes`
+    #[serde(serialize_with = "serialize_bank_hash_stakes")]
+    pub competing_bank_hashes: Vec<(Hash, u128)>,
+    pub is_supermajority: bool,
+    /// `is_supermajority` as a level, with the stake ratio so far when it 
+    /// isnt met, eg. to report early progress towards finality
+    pub confidence: FinalityConfidence,
+    /// how long each stage of the verification took
+    pub timings: StageTimings,
+}
+
+/// time spent in each stage of a verification. stages which didnt run (eg. 
+/// fetching the transaction when verifying a slot) are zero, as are the 
+/// merkle and PoH stages without the `native` feature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StageTimings { 
+    /// fetching the transaction's slot, or for `submit_and_verify` sending 
+    /// it and waiting for it to be confirmed
+    pub fetch_transaction: Duration,
+    /// checking the cluster version and fetching the block headers
+    pub fetch_block_headers: Duration,
+    pub verify_merkle: Duration,
+    /// verifying the PoH chain and recomputing the bank hash
+    pub verify_poh: Duration,
+    /// fetching the block to check it against the headers
+    pub fetch_block: Duration,
+    /// fetching vote accounts and scanning the following slots for votes
+    pub scan_votes: Duration,
+}
+
+impl StageTimings { 
+    pub fn total(&self) -> Duration { 
+        self.fetch_transaction
+            + self.fetch_block_headers
+            + self.verify_merkle
+            + self.verify_poh
+            + self.fetch_block
+            + self.scan_votes
+    }
+}
+
+// the time `f` takes. zero without the `native` feature, as its targets 
+// (eg. wasm32-unknown-unknown) may have no clock
+fn timed<R>(f: impl FnOnce() -> R) -> (R, Duration) { 
+    #[cfg(feature = "native")]
+    { 
+        let started = Instant::now();
+        let result = f();
+        (result, started.elapsed())
+    }
+    #[cfg(not(feature = "native"))]
+    { 
+        (f(), Duration::ZERO)
+    }
+}
+
+// hashes and signatures are serialized as base58 strings rather than bytes
+pub(crate) fn serialize_base58<T: fmt::Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> { 
+    serializer.collect_str(value)
+}
+
+// the supermajority threshold as its fraction, eg. "2/3"
+fn serialize_display<T: fmt::Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> { 
+    serializer.collect_str(value)
+}
+
+fn serialize_option_base58<T: fmt::Display, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> { 
+    match value { 
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_base58_seq<T: fmt::Display, S: Serializer>(value: &[T], serializer: S) -> Result<S::Ok, S::Error> { 
+    serializer.collect_seq(value.iter().map(|x| x.to_string()))
+}
+
+fn serialize_bank_hash_stakes<S: Serializer>(value: &[(Hash, u128)], serializer: S) -> Result<S::Ok, S::Error> { 
+    serializer.collect_seq(value.iter().map(|(bank_hash, stake)| (bank_hash.to_string(), stake)))
+}
+
+/// deserialize a `getBlockHeaders` result. a header without entries fails 
+/// with `EmptyBlockHeader`. the headers dont carry the epoch accounts hash, 
+/// see `LightClient::with_epoch_accounts_hash`
+pub fn deserialize_block_header(bytes: &[u8]) -> Result<BlockHeader, LightNodeError> { 
+    let block_header: BlockHeader = bincode::deserialize(bytes)?;
+    check_entries(&block_header)?;
+    Ok(block_header)
+}
+
+// an empty PoH chain would verify vacuously, ending at the start blockhash
+fn check_entries(block_header: &BlockHeader) -> Result<(), LightNodeError> { 
+    if block_header.entries.is_empty() { 
+        return Err(LightNodeError::EmptyBlockHeader);
+    }
+    Ok(())
+}
+
+/// outcome of verifying one slot of `verify_slot_range`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotVerification { 
+    Verified(Box<VerificationReport>),
+    /// the slot has no block
+    Skipped,
+}
+
+/// inclusion result of `verify_inclusion`, without any vote checks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TxInclusionProof { 
+    pub slot: u64,
+    #[serde(serialize_with = "serialize_base58")]
+    pub signature: Signature,
+    /// index of the entry whose merkle proof includes the signature
+    pub entry_index: Option<usize>,
+    pub poh_verified: bool,
+    /// recomputed bank hash, set once the PoH chain is verified
+    #[serde(serialize_with = "serialize_option_base58")]
+    pub bank_hash: Option<Hash>,
+}
+
+impl TxInclusionProof { 
+    pub fn tx_included(&self) -> bool { 
+        self.entry_index.is_some()
+    }
+}
+
+/// verify `signature`'s merkle inclusion, the PoH chain and recompute the 
+/// bank hash of a block header obtained by any means, eg. from an archival 
+/// service. `epoch_accounts_hash` is needed on the slot whose bank mixes it 
+/// in, see `LightClient::with_epoch_accounts_hash`. votes arent checked, see `verify_finality`. 
+/// with the block's `signature_count` (eg. from `getBlock`) the header's is 
+/// checked against it first, see `check_signature_count`. a header without 
+/// entries fails with `EmptyBlockHeader`
+pub fn verify_block_header(
+    slot: u64,
+    block_header: &BlockHeader,
+    epoch_accounts_hash: Option<Hash>,
+    signature_count: Option<u64>,
+    signature: Signature,
+) -> Result<VerificationReport, LightNodeError> { 
+    verify_block_header_with_scheme(slot, block_header, epoch_accounts_hash, signature_count, signature, &EpochAccountsHashBankHash)
+}
+
+/// `verify_block_header` for a cluster whose runtime computes the bank hash 
+/// with `bank_hash_scheme`
+pub fn verify_block_header_with_scheme(
+    slot: u64,
+    block_header: &BlockHeader,
+    epoch_accounts_hash: Option<Hash>,
+    signature_count: Option<u64>,
+    signature: Signature,
+    bank_hash_scheme: &dyn BankHashScheme,
+) -> Result<VerificationReport, LightNodeError> { 
+    check_entries(block_header)?;
+    if let Some(signature_count) = signature_count { 
+        check_signature_count(slot, block_header, signature_count)?;
+    }
+    let mut report = VerificationReport::new(slot, Some(signature));
+
+    // find and verify tx signature in entry
+    let (entry, verify_merkle) = timed(|| find_tx_entry(block_header.start_blockhash, &block_header.entries, &signature));
+    report.tx_included = entry.is_some();
+    report.timings.verify_merkle = verify_merkle;
+    debug!(tx_included = report.tx_included, "verified merkle inclusion");
+
+    let (hashes, verify_poh) = timed(|| verify_header_bank_hash(block_header, epoch_accounts_hash, &signature, bank_hash_scheme));
+    report.timings.verify_poh = verify_poh;
+    match hashes { 
+        Ok((blockhash, bank_hash)) => { 
+            report.poh_verified = true;
+            report.blockhash = blockhash;
+            report.bank_hash = bank_hash;
+        }
+        Err(e) => warn!("poh verification failed: {}", e),
+    }
+
+    Ok(report)
+}
+
+/// how far verification got, reported when it times out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyStage { 
+    /// waiting for the transaction to be confirmed
+    FetchingTransaction,
+    /// the transaction is confirmed, waiting for its block headers
+    FetchingBlockHeaders { slot: u64 },
+    /// the transaction and bank hash verified, waiting for votes to land
+    ScanningVotes { slot: u64, bank_hash: Hash },
+}
+
+impl fmt::Display for VerifyStage { 
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { 
+        match self { 
+            VerifyStage::FetchingTransaction => write!(f, "transaction never confirmed"),
+            VerifyStage::FetchingBlockHeaders { slot } => { 
+                write!(f, "block headers @ slot {} never arrived", slot)
+            }
+            VerifyStage::ScanningVotes { slot, bank_hash } => { 
+                write!(f, "bank hash {} @ slot {} verified but votes never landed", bank_hash, slot)
+            }
+        }
+    }
+}
+
+/// votes on a slot's bank hash, from `verify_finality`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinalityReport { 
+    pub slot: u64,
+    pub slot_epoch: u64,
+    pub stake_epoch: u64,
+    pub bank_hash: Hash,
+    /// stake which voted for `bank_hash`
+    pub voted_stake: u128,
+    pub total_stake: u128,
+    pub current_total_stake: u128,
+    pub delinquent_stake: u128,
+    /// the stake `is_supermajority` was checked against
+    pub threshold_stake: u128,
+    /// `voted_stake / threshold_stake`, rounded
+    pub voted_stake_ratio: f64,
+    pub supermajority_threshold: SupermajorityThreshold,
+    pub unattributed_votes: usize,
+    /// vote accounts whose vote's signatures didnt verify
+    pub signature_verification_failures: Vec<Pubkey>,
+    /// slots without a block in the window scanned for votes
+    pub skipped_slots: Vec<u64>,
+    /// slots in the window whose block wasnt available yet, see 
+    /// `VoteTally::unavailable_slots`
+    pub unavailable_slots: Vec<u64>,
+    /// number of slots scanned for votes
+    pub slots_scanned: u64,
+    /// stake which voted on the slot, for any bank hash
+    pub collected_stake: u128,
+    /// bank hashes with significant stake when the votes are split
+    pub competing_bank_hashes: Vec<(Hash, u128)>,
+    pub is_supermajority: bool,
+}
+
+/// verify the header's entries form a PoH chain from its start blockhash, 
+/// and recompute the bank hash from the last entry. returns the last entry 
+/// hash (the blockhash) and the bank hash
+pub(crate) fn verify_header_bank_hash(
+    block_headers: &BlockHeader,
+    epoch_accounts_hash: Option<Hash>,
+    tx_sig: &Signature,
+    bank_hash_scheme: &dyn BankHashScheme,
+) -> Result<(Hash, Hash), PohError> { 
+    let last_blockhash = verify_entry_chain(block_headers.start_blockhash, &block_headers.entries, tx_sig)?;
+    debug!(%last_blockhash, "verified poh chain");
+
+    let bank_hash = bank_hash_scheme.bank_hash(&BankHashInputs { 
+        parent_hash: block_headers.parent_hash,
+        accounts_delta_hash: block_headers.accounts_delta_hash,
+        signature_count: header_signature_count(block_headers),
+        last_blockhash,
+        epoch_accounts_hash,
+    });
+    debug!(%bank_hash, "recomputed bank hash");
+    Ok((last_blockhash, bank_hash))
+}
+
+/// the block's signature count from the header, which the runtime 
+/// serializes as a little endian u64
+pub fn header_signature_count(block_header: &BlockHeader) -> u64 { 
+    u64::from_le_bytes(block_header.signature_count_buf)
+}
+
+/// check the header's signature count is the block's `signature_count`. 
+/// the bank hash is recomputed from the header's bytes, so a wrong count (or 
+/// one encoded big endian) would otherwise only show as a wrong bank hash
+pub fn check_signature_count(slot: u64, block_header: &BlockHeader, signature_count: u64) -> Result<(), LightNodeError> { 
+    let got = header_signature_count(block_header);
+    if got != signature_count { 
+        if u64::from_be_bytes(block_header.signature_count_buf) == signature_count { 
+            warn!(slot, "header signature count is big endian, expected little endian");
+        }
+        return Err(LightNodeError::SignatureCountMismatch { slot, expected: signature_count, got });
+    }
+    Ok(())
+}
+
+/// the header and each of its entries in order, one per line, eg. to 
+/// compare against a validator's ledger when PoH verification fails. an 
+/// entry's mixed in hash is the transaction hash of a partial entry or the 
+/// proof root of a merkle entry, which has none when it holds a single 
+/// transaction
+pub fn describe_block_header(block_header: &BlockHeader) -> String { 
+    let mut out = String::new();
+    let _ = writeln!(out, "start blockhash: {}", block_header.start_blockhash);
+    let _ = writeln!(out, "parent hash: {}", block_header.parent_hash);
+    let _ = writeln!(out, "accounts delta hash: {}", block_header.accounts_delta_hash);
+    let _ = writeln!(out, "signature count: {}", header_signature_count(block_header));
+    let _ = writeln!(out, "entries: {}", block_header.entries.len());
+    for (index, entry) in block_header.entries.iter().enumerate() { 
+        let (num_hashes, mixed_in) = match entry { 
+            EntryProof::PartialEntry(x) => (x.num_hashes, x.transaction_hash),
+            EntryProof::MerkleEntry(x) => (x.num_hashes, x.proof.root()),
+        };
+        let mixed_in = match (mixed_in, entry) { 
+            (Some(mixed_in), _) => mixed_in.to_string(),
+            (None, EntryProof::PartialEntry(_)) => "tick".to_string(),
+            (None, EntryProof::MerkleEntry(_)) => "single tx".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{:>5} {:<7} num_hashes: {:<8} mixed in: {:<44} hash: {}",
+            index,
+            EntryKind::of(entry).to_string(),
+            num_hashes,
+            mixed_in,
+            entry.hash()
+        );
+    }
+    out
+}
+
+/// index of the merkle entry whose proof includes `tx_sig`. the entry hash 
+/// must chain from the previous entry over the proof's root (see 
+/// `PohEntry::from_proof`), so inclusion is proven against the hash PoH 
+/// actually mixed in. a single leaf proof has no branches to verify, so 
+/// there the leaf hash itself must be what the entry mixed in
+fn find_tx_entry(start_blockhash: Hash, entries: &[EntryProof], tx_sig: &Signature) -> Option<usize> { 
+    let mut prev_hash = start_blockhash;
+    for (index, entry) in entries.iter().enumerate() {
+        if let EntryProof::MerkleEntry(x) = entry { 
+            let included = verify_merkle_inclusion(tx_sig.as_ref(), &x.proof)
+                && PohEntry::from_proof(entry, tx_sig).verify(&prev_hash);
+            return included.then_some(index);
+        }
+        prev_hash = entry.hash();
+    }
+    None
+}
+
+impl VerificationReport { 
+    fn new(slot: u64, signature: Option<Signature>) -> Self { 
+        Self { 
+            slot,
+            slot_epoch: 0,
+            stake_epoch: 0,
+            signature,
+            tx_included: false,
+            poh_verified: false,
+            blockhash: Hash::default(),
+            bank_hash: Hash::default(),
+            voted_stake: 0,
+            total_stake: 0,
+            current_total_stake: 0,
+            delinquent_stake: 0,
+            threshold_stake: 0,
+            voted_stake_ratio: 0.0,
+            supermajority_threshold: SupermajorityThreshold::default(),
+            unattributed_votes: 0,
+            signature_verification_failures: vec![],
+            skipped_slots: vec![],
+            unavailable_slots: vec![],
+            slots_scanned: 0,
+            collected_stake: 0,
+            cluster_version: None,
+            commitment: None,
+            competing_bank_hashes: vec![],
+            is_supermajority: false,
+            confidence: FinalityConfidence::default(),
+            timings: StageTimings::default(),
+        }
+    }
+
+    /// whether the votes are for another bank hash than the recomputed one, 
+    /// either split with another one ahead or all of the collected stake
+    pub fn votes_for_other_bank_hash(&self) -> bool { 
+        let competing = self.competing_bank_hashes
+            .first()
+            .map(|(bank_hash, _)| *bank_hash != self.bank_hash)
+            .unwrap_or(false);
+        competing || (self.voted_stake == 0 && self.collected_stake > 0)
+    }
+}
+
+// how often `submit_and_verify` polls for the sent transaction's status
+#[cfg(feature = "native")]
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[cfg(feature = "native")]
+impl<T: RpcTransport> LightClient<T> { 
+    /// fetch the slot of an already confirmed transaction and verify it
+    pub async fn verify_transaction(&self, signature: Signature) -> Result<VerificationReport, LightNodeError> { 
+        let stage = Mutex::new(VerifyStage::FetchingTransaction);
+        self.verify_transaction_in_stages(signature, &stage).await
+    }
+
+    /// `verify_transaction` with a deadline, failing with `VerifyTimeout` 
+    /// and the stage it got to when it expires
+    pub async fn verify_transaction_with_timeout(&self, signature: Signature, timeout: Duration) -> Result<VerificationReport, LightNodeError> { 
+        let stage = Mutex::new(VerifyStage::FetchingTransaction);
+        match tokio::time::timeout(timeout, self.verify_transaction_in_stages(signature, &stage)).await { 
+            Ok(result) => result,
+            Err(_) => Err(LightNodeError::VerifyTimeout { stage: stage.into_inner().unwrap(), timeout }),
+        }
+    }
+
+    // verify_transaction, recording each stage as it starts
+    async fn verify_transaction_in_stages(&self, signature: Signature, stage: &Mutex<VerifyStage>) -> Result<VerificationReport, LightNodeError> { 
+        *stage.lock().unwrap() = VerifyStage::FetchingTransaction;
+        let started = Instant::now();
+        let slot = self.get_tx(signature).await?.result.slot;
+        let fetch_transaction = started.elapsed();
+        let mut report = self.verify_slot_in_stages(slot, signature, stage).await?;
+        report.timings.fetch_transaction = fetch_transaction;
+        Ok(report)
+    }
+
+    /// build a transaction from `instructions`, paid for by the first of 
+    /// `signers`, send it, wait for it to be confirmed and verify its slot: 
+    /// an end to end check of the cluster and the light client. bounded by 
+    /// `timeout`, failing with `VerifyTimeout` and the stage it got to. 
+    /// 
+    /// a transaction whose blockhash is already too old when it is sent 
+    /// fails with `TransactionExpired`, one which expires before it lands 
+    /// with `TransactionDropped`, and one which lands with an error with 
+    /// `TransactionFailed`. verification failures are in the report, as for 
+    /// `verify_transaction`
+    pub async fn submit_and_verify<S: Signers + ?Sized>(&self, instructions: &[Instruction], signers: &S, timeout: Duration) -> Result<VerificationReport, LightNodeError> { 
+        let stage = Mutex::new(VerifyStage::FetchingTransaction);
+        let submit_and_verify = async { 
+            let started = Instant::now();
+            let (signature, blockhash) = self.submit(instructions, signers).await?;
+            let slot = self.wait_for_confirmation(signature, blockhash).await?;
+            let fetch_transaction = started.elapsed();
+            let mut report = self.verify_slot_in_stages(slot, signature, &stage).await?;
+            report.timings.fetch_transaction = fetch_transaction;
+            Ok(report)
+        };
+        match tokio::time::timeout(timeout, submit_and_verify).await { 
+            Ok(result) => result,
+            Err(_) => Err(LightNodeError::VerifyTimeout { stage: stage.into_inner().unwrap(), timeout }),
+        }
+    }
+
+    // sign and send a transaction with a fresh blockhash, returning its 
+    // signature and blockhash
+    async fn submit<S: Signers + ?Sized>(&self, instructions: &[Instruction], signers: &S) -> Result<(Signature, Hash), LightNodeError> { 
+        let payer = signers.pubkeys().first().copied();
+        let (blockhash, _) = self.rpc_client().get_latest_blockhash_with_commitment(self.commitment()).await?;
+        let mut tx = Transaction::new_with_payer(instructions, payer.as_ref());
+        tx.try_sign(signers, blockhash)?;
+        let signature = *tx.signatures.first().ok_or(LightNodeError::UnsignedTransaction)?;
+        match self.rpc_client().send_transaction(&tx).await { 
+            Ok(signature) => { 
+                debug!(%signature, %blockhash, "sent tx");
+                Ok((signature, blockhash))
+            }
+            Err(e) if e.get_transaction_error() == Some(TransactionError::BlockhashNotFound) => { 
+                Err(LightNodeError::TransactionExpired { signature, blockhash })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // the slot of a sent transaction once it is confirmed at the commitment 
+    // blocks are fetched at. it is dropped once its blockhash expires 
+    // without it having landed
+    async fn wait_for_confirmation(&self, signature: Signature, blockhash: Hash) -> Result<u64, LightNodeError> { 
+        let commitment = CommitmentConfig { commitment: self.block_commitment() };
+        let mut attempts = 0;
+        loop { 
+            attempts += 1;
+            match self.get_signature_status(signature).await? { 
+                Some(status) => { 
+                    if let Some(err) = status.err { 
+                        return Err(LightNodeError::TransactionFailed { signature, err });
+                    }
+                    if status.satisfies_commitment(commitment) { 
+                        debug!(%signature, slot = status.slot, "tx confirmed");
+                        return Ok(status.slot);
+                    }
+                }
+                None => { 
+                    let valid = self.rpc_client().is_blockhash_valid(&blockhash, CommitmentConfig::processed()).await?;
+                    if !valid { 
+                        return Err(LightNodeError::TransactionDropped { signature, attempts });
+                    }
+                }
+            }
+            tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        }
+    }
+
+    /// verify many transactions with up to `concurrency` verifications in 
+    /// flight at once. the client (its connections and cached vote account 
+    /// stakes) is shared between them, and each result is paired with its 
+    /// signature, in the order the verifications finish
+    pub async fn verify_many(&self, signatures: Vec<Signature>, concurrency: usize) -> Vec<(Signature, Result<VerificationReport, LightNodeError>)> { 
+        stream::iter(signatures)
+            .map(|signature| async move { (signature, self.verify_transaction(signature).await) })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// verify the PoH chain, bank hash and votes of each slot from 
+    /// `start_slot` to `end_slot` (inclusive, at most the confirmed slot) 
+    /// with up to `concurrency` slots in flight, see `verify_block`. the 
+    /// client's caches are shared between the slots, and results are in 
+    /// slot order with slots without a block as `Skipped` 
+    pub async fn verify_slot_range(&self, start_slot: u64, end_slot: u64, concurrency: usize) -> Result<Vec<(u64, Result<SlotVerification, LightNodeError>)>, LightNodeError> { 
+        let confirmed_slots = self.get_confirmed_blocks(start_slot, end_slot).await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let confirmed_slots = &confirmed_slots;
+        let results = stream::iter(start_slot..=end_slot)
+            .map(|slot| async move { 
+                if !confirmed_slots.contains(&slot) { 
+                    return (slot, Ok(SlotVerification::Skipped));
+                }
+                let result = match self.verify_block(slot).await { 
+                    Ok(report) => Ok(SlotVerification::Verified(Box::new(report))),
+                    // eg. pruned since it was listed
+                    Err(LightNodeError::SlotSkipped { .. } | LightNodeError::BlockMissing { .. }) => Ok(SlotVerification::Skipped),
+                    Err(e) => Err(e),
+                };
+                (slot, result)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        Ok(results)
+    }
+
+    /// `verify_slot` with a deadline, failing with `VerifyTimeout` and the 
+    /// stage it got to when it expires
+    pub async fn verify_slot_with_timeout(&self, slot: u64, tx_sig: Signature, timeout: Duration) -> Result<VerificationReport, LightNodeError> { 
+        let stage = Mutex::new(VerifyStage::FetchingBlockHeaders { slot });
+        match tokio::time::timeout(timeout, self.verify_slot_in_stages(slot, tx_sig, &stage)).await { 
+            Ok(result) => result,
+            Err(_) => Err(LightNodeError::VerifyTimeout { stage: stage.into_inner().unwrap(), timeout }),
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn verify_slot(&self, slot: u64, tx_sig: Signature) -> Result<VerificationReport, LightNodeError> { 
+        let stage = Mutex::new(VerifyStage::FetchingBlockHeaders { slot });
+        self.verify_slot_in_stages(slot, tx_sig, &stage).await
+    }
+
+    // verify_slot, recording each stage as it starts
+    async fn verify_slot_in_stages(&self, slot: u64, tx_sig: Signature, stage: &Mutex<VerifyStage>) -> Result<VerificationReport, LightNodeError> { 
+        *stage.lock().unwrap() = VerifyStage::FetchingBlockHeaders { slot };
+        let started = Instant::now();
+        let cluster_version = self.check_cluster_version().await?;
+
+        // get headers
+        let block_headers = self.get_block_headers(slot, tx_sig).await?.result;
+        let block_headers = deserialize_block_header(&block_headers)?;
+        let fetch_block_headers = started.elapsed();
+
+        let mut report = verify_block_header_with_scheme(slot, &block_headers, self.epoch_accounts_hash(slot), None, tx_sig, self.bank_hash_scheme())?;
+        report.cluster_version = cluster_version;
+        report.timings.fetch_block_headers = fetch_block_headers;
+        if report.tx_included && report.poh_verified { 
+            let started = Instant::now();
+            self.check_block_summary(slot, &block_headers, report.blockhash).await?;
+            report.timings.fetch_block = started.elapsed();
+
+            *stage.lock().unwrap() = VerifyStage::ScanningVotes { slot, bank_hash: report.bank_hash };
+            let started = Instant::now();
+            self.apply_finality(&mut report).await?;
+            report.timings.scan_votes = started.elapsed();
+        }
+        Ok(report)
+    }
+
+    /// verify the PoH chain, bank hash and votes of a slot without proving 
+    /// a transaction's inclusion. `getBlockHeaders` needs a signature, so 
+    /// the headers are requested for the block's first transaction
+    #[instrument(skip(self))]
+    pub async fn verify_block(&self, slot: u64) -> Result<VerificationReport, LightNodeError> { 
+        let mut report = VerificationReport::new(slot, None);
+        let started = Instant::now();
+        report.cluster_version = self.check_cluster_version().await?;
+
+        let tx_sig = match self.get_block_signatures(slot).await?.first() { 
+            Some(tx_sig) => *tx_sig,
+            // nothing to request the headers with
+            None => return Ok(report),
+        };
+        let block_headers = self.get_block_headers(slot, tx_sig).await?.result;
+        let block_headers = deserialize_block_header(&block_headers)?;
+        report.timings.fetch_block_headers = started.elapsed();
+
+        let (hashes, verify_poh) = timed(|| verify_header_bank_hash(&block_headers, self.epoch_accounts_hash(slot), &tx_sig, self.bank_hash_scheme()));
+        report.timings.verify_poh = verify_poh;
+        (report.blockhash, report.bank_hash) = match hashes { 
+            Ok(hashes) => hashes,
+            Err(e) => { 
+                warn!("poh verification failed: {}", e);
+                return Ok(report);
+            }
+        };
+        report.poh_verified = true;
+        let started = Instant::now();
+        self.check_block_summary(slot, &block_headers, report.blockhash).await?;
+        report.timings.fetch_block = started.elapsed();
+
+        let started = Instant::now();
+        self.apply_finality(&mut report).await?;
+        report.timings.scan_votes = started.elapsed();
+        Ok(report)
+    }
+
+    /// whether `slot` is final, without a transaction: verify the PoH chain 
+    /// of its block headers, recompute the bank hash and scan the votes for 
+    /// it. unlike `verify_block` a PoH failure is an error, as there is no 
+    /// bank hash to check the votes of
+    #[instrument(skip(self))]
+    pub async fn verify_slot_finality(&self, slot: u64) -> Result<FinalityReport, LightNodeError> { 
+        self.check_cluster_version().await?;
+
+        // getBlockHeaders needs a signature, any of the block's will do
+        let tx_sig = *self.get_block_signatures(slot)
+            .await?
+            .first()
+            .ok_or(LightNodeError::NoTransactions { slot })?;
+        let block_headers = self.get_block_headers(slot, tx_sig).await?.result;
+        let block_headers = deserialize_block_header(&block_headers)?;
+
+        let (blockhash, bank_hash) = verify_header_bank_hash(&block_headers, self.epoch_accounts_hash(slot), &tx_sig, self.bank_hash_scheme())?;
+        self.check_block_summary(slot, &block_headers, blockhash).await?;
+
+        self.verify_finality(slot, bank_hash).await
+    }
+
+    // anchor the locally verified header to the block the rpc reports: the 
+    // PoH chain must run from the parent's blockhash to the block's, over 
+    // as many signatures as the block holds
+    async fn check_block_summary(&self, slot: u64, block_header: &BlockHeader, blockhash: Hash) -> Result<(), LightNodeError> { 
+        let summary = self.get_block_summary(slot).await?;
+        if blockhash != summary.blockhash { 
+            return Err(LightNodeError::BlockhashMismatch { slot, expected: summary.blockhash, got: blockhash });
+        }
+        if block_header.start_blockhash != summary.previous_blockhash { 
+            return Err(LightNodeError::StartBlockhashMismatch { 
+                slot,
+                expected: summary.previous_blockhash,
+                got: block_header.start_blockhash,
+            });
+        }
+        check_signature_count(slot, block_header, summary.signature_count)?;
+        debug!(%blockhash, signature_count = summary.signature_count, "header matches getBlock");
+        Ok(())
+    }
+
+    // vote checks on the report's bank hash, shared by the verify methods
+    async fn apply_finality(&self, report: &mut VerificationReport) -> Result<(), LightNodeError> { 
+        let finality = self.verify_finality(report.slot, report.bank_hash).await?;
+        report.slot_epoch = finality.slot_epoch;
+        report.stake_epoch = finality.stake_epoch;
+        report.voted_stake = finality.voted_stake;
+        report.total_stake = finality.total_stake;
+        report.current_total_stake = finality.current_total_stake;
+        report.delinquent_stake = finality.delinquent_stake;
+        report.threshold_stake = finality.threshold_stake;
+        report.voted_stake_ratio = finality.voted_stake_ratio;
+        report.supermajority_threshold = finality.supermajority_threshold;
+        report.unattributed_votes = finality.unattributed_votes;
+        report.signature_verification_failures = finality.signature_verification_failures;
+        report.skipped_slots = finality.skipped_slots;
+        report.unavailable_slots = finality.unavailable_slots;
+        report.slots_scanned = finality.slots_scanned;
+        report.collected_stake = finality.collected_stake;
+        report.competing_bank_hashes = finality.competing_bank_hashes;
+        report.is_supermajority = finality.is_supermajority;
+        report.commitment = Some(self.slot_commitment(report.slot).await?);
+        report.confidence = FinalityConfidence::new(report.is_supermajority, report.voted_stake_ratio, report.commitment);
+
+        Ok(())
+    }
+
+    /// verify a transaction is included in its slot's block headers, that the 
+    /// entries form a PoH chain and recompute the bank hash, without waiting 
+    /// for votes. check the bank hash with `verify_finality` when needed
+    pub async fn verify_inclusion(&self, signature: Signature) -> Result<TxInclusionProof, LightNodeError> { 
+        let slot = self.get_tx(signature).await?.result.slot;
+        self.verify_inclusion_in_slot(slot, signature).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn verify_inclusion_in_slot(&self, slot: u64, signature: Signature) -> Result<TxInclusionProof, LightNodeError> { 
+        let (proof, _) = self.inclusion_proof(slot, signature).await?;
+        Ok(proof)
+    }
+
+    /// verify the inclusion of the transaction at `tx_index` in the block's 
+    /// `getBlock` transaction order, and that its merkle proof is the entry 
+    /// at `entry_index` of the block headers, eg. to relate the order of 
+    /// `getBlock` to the entries the headers expose. the leaf is recomputed 
+    /// from the fetched transaction's first signature. when the transaction 
+    /// is in another entry the proof's `entry_index` is `None`
+    #[instrument(skip(self))]
+    pub async fn verify_inclusion_by_index(&self, slot: u64, entry_index: usize, tx_index: usize) -> Result<TxInclusionProof, LightNodeError> { 
+        let signatures = self.get_block_signatures(slot).await?;
+        let signature = *signatures
+            .get(tx_index)
+            .ok_or(LightNodeError::TransactionIndexOutOfRange { slot, tx_index, transactions: signatures.len() })?;
+
+        let (mut proof, entries) = self.inclusion_proof(slot, signature).await?;
+        if entry_index >= entries { 
+            return Err(LightNodeError::EntryIndexOutOfRange { slot, entry_index, entries });
+        }
+        if proof.entry_index != Some(entry_index) { 
+            warn!(%signature, entry_index, included_in = ?proof.entry_index, "tx isnt included in the entry");
+            proof.entry_index = None;
+        }
+        Ok(proof)
+    }
+
+    // the inclusion proof and the number of entries in the block headers
+    async fn inclusion_proof(&self, slot: u64, signature: Signature) -> Result<(TxInclusionProof, usize), LightNodeError> { 
+        self.check_cluster_version().await?;
+        self.header_inclusion_proof(slot, signature).await
+    }
+
+    // `inclusion_proof` once the cluster version is checked
+    async fn header_inclusion_proof(&self, slot: u64, signature: Signature) -> Result<(TxInclusionProof, usize), LightNodeError> { 
+        let block_headers = self.get_block_headers(slot, signature).await?.result;
+        let block_headers = deserialize_block_header(&block_headers)?;
+
+        let entry_index = find_tx_entry(block_headers.start_blockhash, &block_headers.entries, &signature);
+        debug!(tx_included = entry_index.is_some(), "verified merkle inclusion");
+        let hashes = verify_header_bank_hash(&block_headers, self.epoch_accounts_hash(slot), &signature, self.bank_hash_scheme())
+            .map_err(|e| warn!("poh verification failed: {}", e))
+            .ok();
+        // the bank hash is only as good as the header it was recomputed from
+        if let Some((blockhash, _)) = hashes { 
+            self.check_block_summary(slot, &block_headers, blockhash).await?;
+        }
+        let bank_hash = hashes.map(|(_, bank_hash)| bank_hash);
+
+        let proof = TxInclusionProof { 
+            slot,
+            signature,
+            entry_index,
+            poh_verified: bank_hash.is_some(),
+            bank_hash,
+        };
+        Ok((proof, block_headers.entries.len()))
+    }
+
+    /// tally the votes on `slot` and check whether `bank_hash` has a 
+    /// supermajority of stake, scanning the client's vote window. with a 
+    /// geyser endpoint (`geyser` feature) the blocks are streamed from it
+    pub async fn verify_finality(&self, slot: u64, bank_hash: Hash) -> Result<FinalityReport, LightNodeError> { 
+        self.verify_finality_with_window(slot, bank_hash, self.vote_window()).await
+    }
+
+    /// `verify_finality` scanning `vote_window` instead of the client's
+    #[instrument(skip(self))]
+    pub async fn verify_finality_with_window(&self, slot: u64, bank_hash: Hash, vote_window: VoteWindow) -> Result<FinalityReport, LightNodeError> { 
+        // parse votes from the next blocks
+        let vote_tally = match vote_window { 
+            #[cfg(feature = "geyser")]
+            VoteWindow::Fixed(slots_ahead) if self.geyser().is_some() => self.parse_geyser_votes(slot, None, slots_ahead).await?,
+            #[cfg(feature = "geyser")]
+            VoteWindow::Adaptive { max_slots } if self.geyser().is_some() => self.parse_geyser_votes(slot, Some(bank_hash), max_slots).await?,
+            VoteWindow::Fixed(slots_ahead) => self.parse_block_votes(slot, slots_ahead).await?,
+            VoteWindow::Adaptive { max_slots } => self.parse_block_votes_until(slot, bank_hash, max_slots).await?,
+        };
+        let threshold_stake = vote_tally.threshold_stake(self.supermajority_stake());
+        let voted_stake = vote_tally.stake_for(&bank_hash);
+        let collected_stake = vote_tally.collected_stake();
+
+        // voted_stake >= 2/3 * threshold_stake by default, compared in integers
+        let supermajority_threshold = self.supermajority_threshold();
+        let is_supermajority = supermajority_threshold.is_met(voted_stake, threshold_stake);
+        let voted_stake_ratio = stake_ratio(voted_stake, threshold_stake);
+        debug!(voted_stake, threshold_stake, voted_stake_ratio, %supermajority_threshold, is_supermajority, "tallied votes");
+        let competing_bank_hashes = vote_tally.competing_bank_hashes();
+        if !competing_bank_hashes.is_empty() { 
+            warn!(?competing_bank_hashes, "stake is split between bank hashes");
+        }
+
+        Ok(FinalityReport { 
+            slot,
+            slot_epoch: vote_tally.slot_epoch,
+            stake_epoch: vote_tally.stake_epoch,
+            bank_hash,
+            voted_stake,
+            total_stake: vote_tally.total_stake,
+            current_total_stake: vote_tally.current_total_stake,
+            delinquent_stake: vote_tally.delinquent_stake,
+            threshold_stake,
+            voted_stake_ratio,
+            supermajority_threshold,
+            unattributed_votes: vote_tally.unattributed_votes,
+            signature_verification_failures: vote_tally.signature_verification_failures,
+            skipped_slots: vote_tally.skipped_slots,
+            unavailable_slots: vote_tally.unavailable_slots,
+            slots_scanned: vote_tally.slots_scanned,
+            collected_stake,
+            competing_bank_hashes,
+            is_supermajority,
+        })
+    }
+
+    /// verify the inclusion of several transactions in the same slot with up 
+    /// to `concurrency` header requests in flight, with a result per distinct 
+    /// signature in the order given. the cluster version and the block's 
+    /// signatures are fetched once, so a signature which isnt in the block 
+    /// fails with `TxNotIncluded` without requesting headers. `getBlockHeaders` 
+    /// builds the merkle proof for a single signature, so the rest each 
+    /// fetch their own. a signature which fails doesnt fail the others
+    pub async fn verify_transactions_in_slot(&self, slot: u64, signatures: &[Signature], concurrency: usize) -> Result<Vec<(Signature, Result<TxInclusionProof, LightNodeError>)>, LightNodeError> { 
+        self.check_cluster_version().await?;
+        let block_signatures = self.get_block_signatures(slot).await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let block_signatures = &block_signatures;
+
+        let mut seen = HashSet::new();
+        let signatures = signatures.iter().copied().filter(|signature| seen.insert(*signature));
+        let results = stream::iter(signatures)
+            .map(|signature| async move { 
+                if !block_signatures.contains(&signature) { 
+                    return (signature, Err(LightNodeError::TxNotIncluded { signature }));
+                }
+                let proof = self.header_inclusion_proof(slot, signature).await.map(|(proof, _)| proof);
+                (signature, proof)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use serde_json::json;
+    use solana_sdk::hash::hashv;
+
+    #[cfg(feature = "native")]
+    use crate::client::tests::{block_json, test_client};
+    use crate::merkle::transaction_leaf_hash;
+    use crate::poh::next_hash_with_tx_hash;
+    #[cfg(feature = "native")]
+    use crate::transport::MemoryTransport;
+
+    use super::*;
+
+    // built from its serde form, which names the variant rather than the 
+    // entry struct. `proof` is the serde form of a `SolidProof`: its path of 
+    // (node, left sibling, right sibling) up to the root
+    fn merkle_entry(num_hashes: u64, hash: Hash, proof: serde_json::Value) -> EntryProof { 
+        serde_json::from_value(json!({ 
+            "MerkleEntry": { "num_hashes": num_hashes, "hash": hash, "proof": proof },
+        })).unwrap()
+    }
+
+    fn block_header(start_blockhash: Hash, entries: Vec<EntryProof>) -> BlockHeader { 
+        BlockHeader { 
+            start_blockhash,
+            parent_hash: Hash::new_unique(),
+            accounts_delta_hash: Hash::new_unique(),
+            signature_count_buf: 1u64.to_le_bytes(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn test_deserialize_block_header_propagates_errors() { 
+        let block_header = BlockHeader { 
+            start_blockhash: Hash::new_unique(),
+            parent_hash: Hash::new_unique(),
+            accounts_delta_hash: Hash::new_unique(),
+            signature_count_buf: 7u64.to_le_bytes(),
+            entries: vec![],
+        };
+        let bytes = bincode::serialize(&block_header).unwrap();
+        // truncated, eg. a response cut short
+        assert!(matches!(deserialize_block_header(&bytes[..40]), Err(LightNodeError::Bincode(_))));
+        assert!(matches!(deserialize_block_header(&[]), Err(LightNodeError::Bincode(_))));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_verify_transactions_in_slot_reports_each_signature() { 
+        let (in_block, missing) = (Signature::new_unique(), Signature::new_unique());
+        let mut block = block_json(5, &[]);
+        block["signatures"] = json!([in_block.to_string()]);
+        let transport = MemoryTransport::default()
+            .with_result("getBlock", 5, block)
+            .with_error("getBlockHeaders", 5, -32602, "Invalid params");
+        let light_client = test_client(transport);
+
+        let results = light_client.verify_transactions_in_slot(5, &[missing, in_block, missing], 4).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, missing);
+        assert!(matches!(results[0].1, Err(LightNodeError::TxNotIncluded { .. })));
+        assert_eq!(results[1].0, in_block);
+        assert!(matches!(results[1].1, Err(LightNodeError::JsonRpc { code: -32602, .. })));
+        // the block is fetched once, and headers only for the signature in it
+        assert_eq!(light_client.transport().calls("getBlock"), 1);
+        assert_eq!(light_client.transport().calls("getBlockHeaders"), 1);
+    }
+
+    #[test]
+    fn test_single_leaf_entry_rejects_wrong_signature() { 
+        let (signature, wrong_signature) = (Signature::new_unique(), Signature::new_unique());
+
+        // an entry holding only `signature` has no branches, and mixes in its leaf hash
+        let start_blockhash = Hash::new_unique();
+        let leaf_hash = transaction_leaf_hash(&signature);
+        let entry = merkle_entry(3, next_hash_with_tx_hash(&start_blockhash, 3, Some(leaf_hash)), json!([]));
+        let block_header = block_header(start_blockhash, vec![entry]);
+
+        let report = verify_block_header(5, &block_header, None, None, signature).unwrap();
+        assert!(report.tx_included);
+        assert!(report.poh_verified);
+        let report = verify_block_header(5, &block_header, None, None, wrong_signature).unwrap();
+        assert!(!report.tx_included);
+    }
+
+    #[test]
+    fn test_proof_root_must_be_the_mixed_in_hash() { 
+        let (signature, other) = (Signature::new_unique(), Signature::new_unique());
+        let (leaf, sibling) = (transaction_leaf_hash(&signature), transaction_leaf_hash(&other));
+        let root = hashv(&[&[1], leaf.as_ref(), sibling.as_ref()]);
+        let proof = json!([[root, null, sibling]]);
+        let start_blockhash = Hash::new_unique();
+
+        let entry = merkle_entry(2, next_hash_with_tx_hash(&start_blockhash, 2, Some(root)), proof.clone());
+        let report = verify_block_header(5, &block_header(start_blockhash, vec![entry]), None, None, signature).unwrap();
+        assert!(report.tx_included);
+
+        // the proof is valid, but the entry hash chains over another hash
+        let entry = merkle_entry(2, next_hash_with_tx_hash(&start_blockhash, 2, Some(Hash::new_unique())), proof);
+        let report = verify_block_header(5, &block_header(start_blockhash, vec![entry]), None, None, signature).unwrap();
+        assert!(!report.tx_included);
+    }
+
+    #[test]
+    fn test_block_header_signature_count_is_checked() { 
+        let start_blockhash = Hash::new_unique();
+        let tick: EntryProof = serde_json::from_value(json!({ 
+            "PartialEntry": { "num_hashes": 0, "hash": start_blockhash, "transaction_hash": null },
+        })).unwrap();
+        let mut block_header = block_header(start_blockhash, vec![tick]);
+        block_header.signature_count_buf = 3u64.to_le_bytes();
+
+        let report = verify_block_header(5, &block_header, None, Some(3), Signature::new_unique()).unwrap();
+        assert!(report.poh_verified);
+        assert!(matches!(
+            verify_block_header(5, &block_header, None, Some(4), Signature::new_unique()),
+            Err(LightNodeError::SignatureCountMismatch { slot: 5, expected: 4, got: 3 })
+        ));
+
+        // big endian bytes dont parse as the block's count either
+        block_header.signature_count_buf = 3u64.to_be_bytes();
+        assert!(matches!(
+            check_signature_count(5, &block_header, 3),
+            Err(LightNodeError::SignatureCountMismatch { expected: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_empty_block_header() { 
+        let block_header = BlockHeader { 
+            start_blockhash: Hash::new_unique(),
+            parent_hash: Hash::new_unique(),
+            accounts_delta_hash: Hash::new_unique(),
+            signature_count_buf: 0u64.to_le_bytes(),
+            entries: vec![],
+        };
+        let bytes = bincode::serialize(&block_header).unwrap();
+        assert!(matches!(deserialize_block_header(&bytes), Err(LightNodeError::EmptyBlockHeader)));
+        assert!(matches!(verify_block_header(5, &block_header, None, None, Signature::new_unique()), Err(LightNodeError::EmptyBlockHeader)));
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fmt, str::FromStr};
+
+/// `major.minor.patch` of a cluster's runtime, eg. `solana-core` of `getVersion`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClusterVersion { 
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// the oldest version `getBlockHeaders` and the bank hash formula are known 
+/// to work with
+pub const MIN_SUPPORTED_VERSION: ClusterVersion = ClusterVersion { major: 1, minor: 16, patch: 0 };
+/// the first version which isnt known to work (exclusive)
+pub const MAX_SUPPORTED_VERSION: ClusterVersion = ClusterVersion { major: 1, minor: 17, patch: 0 };
+
+impl ClusterVersion { 
+    pub fn is_supported(&self) -> bool { 
+        MIN_SUPPORTED_VERSION <= *self && *self < MAX_SUPPORTED_VERSION
+    }
+}
+
+impl FromStr for ClusterVersion { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        // the patch can have a suffix, eg. `1.16.14-rc1`
+        let mut parts = s.splitn(3, '.').map(|part| { 
+            let digits = part.split(|c: char| !c.is_ascii_digit()).next().unwrap_or_default();
+            digits.parse::<u64>().map_err(|_| format!("invalid version '{}'", s))
+        });
+        let mut next = || parts.next().unwrap_or_else(|| Err(format!("invalid version '{}'", s)));
+        Ok(ClusterVersion { major: next()?, minor: next()?, patch: next()? })
+    }
+}
+
+impl fmt::Display for ClusterVersion { 
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { 
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// what to do when the cluster runs a version outside the supported range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionCheck { 
+    /// dont request the version at all
+    Off,
+    #[default]
+    Warn,
+    /// fail verification with `UnsupportedVersion`
+    Error,
+}
+
+impl FromStr for VersionCheck { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        match s { 
+            "off" => Ok(VersionCheck::Off),
+            "warn" => Ok(VersionCheck::Warn),
+            "error" => Ok(VersionCheck::Error),
+            _ => Err(format!("unknown version check '{}', expected 'off', 'warn' or 'error'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests { 
+    use super::*;
+
+    #[test]
+    fn test_cluster_version_parsing() { 
+        assert_eq!("1.16.14".parse(), Ok(ClusterVersion { major: 1, minor: 16, patch: 14 }));
+        assert_eq!("1.16.14-rc1".parse(), Ok(ClusterVersion { major: 1, minor: 16, patch: 14 }));
+        assert!("1.16".parse::<ClusterVersion>().is_err());
+        assert!("v1.16.14".parse::<ClusterVersion>().is_err());
+        assert!("".parse::<ClusterVersion>().is_err());
+
+        assert!("1.16.0".parse::<ClusterVersion>().unwrap().is_supported());
+        assert!(!"1.15.2".parse::<ClusterVersion>().unwrap().is_supported());
+        assert!(!"1.17.0".parse::<ClusterVersion>().unwrap().is_supported());
+        assert_eq!(MIN_SUPPORTED_VERSION.to_string(), "1.16.0");
+    }
+}
@@ -0,0 +1,704 @@
+use std::{collections::{HashMap, HashSet}, fmt, str::FromStr};
+
+use serde::Serialize;
+use solana_sdk::{commitment_config::CommitmentLevel, hash::Hash, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction, vote::instruction::VoteInstruction};
+use tracing::warn;
+
+/// stake-weighted votes for the bank hashes of a single slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteTally { 
+    /// only votes whose last voted slot is this slot are counted
+    pub slot: u64,
+    /// epoch of `slot`
+    pub slot_epoch: u64,
+    /// epoch the stakes were fetched in. rpc only serves the current stake, 
+    /// so for a slot from a past epoch this is newer than `slot_epoch` and 
+    /// the stakes are an approximation
+    pub stake_epoch: u64,
+    /// current and delinquent stake
+    pub total_stake: u128,
+    pub current_total_stake: u128,
+    pub delinquent_stake: u128,
+    /// the vote accounts which voted for each bank hash, with their stake
+    pub per_bank_hash: HashMap<Hash, Vec<(Pubkey, u64)>>,
+    /// verified votes from vote accounts with no known stake
+    pub unattributed_votes: usize,
+    /// vote accounts whose vote's signatures didnt verify, so arent counted. 
+    /// empty on a healthy cluster, otherwise a block is corrupt or was 
+    /// decoded wrong
+    pub signature_verification_failures: Vec<Pubkey>,
+    /// slots in the scanned window without a block
+    pub skipped_slots: Vec<u64>,
+    /// slots after the last confirmed one whose block was still unavailable 
+    /// after retrying, so their votes arent counted
+    pub unavailable_slots: Vec<u64>,
+    /// number of slots from `slot` on which were scanned for votes
+    pub slots_scanned: u64,
+}
+
+// share of the total stake a bank hash needs before it counts as competing, 
+// so a handful of votes on a minority fork arent flagged
+const COMPETING_STAKE_DIVISOR: u128 = 20;
+
+/// which stake the supermajority threshold is applied against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SupermajorityStake { 
+    /// all stake, including delinquent validators which arent voting. this 
+    /// is the conservative choice: delinquent stake counts against the slot
+    #[default]
+    Total,
+    /// only the stake of validators which are currently voting
+    Current,
+}
+
+impl FromStr for SupermajorityStake { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        match s { 
+            "total" => Ok(SupermajorityStake::Total),
+            "current" => Ok(SupermajorityStake::Current),
+            _ => Err(format!("unknown supermajority stake '{}', expected 'total' or 'current'", s)),
+        }
+    }
+}
+
+/// share of the threshold stake a bank hash needs to count as having a 
+/// supermajority, `numerator / denominator`. defaults to 2/3, parses from 
+/// eg. `3/4`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupermajorityThreshold { 
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Default for SupermajorityThreshold { 
+    fn default() -> Self { 
+        Self { numerator: 2, denominator: 3 }
+    }
+}
+
+impl SupermajorityThreshold { 
+    /// `None` unless `0 < numerator <= denominator`
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> { 
+        (numerator > 0 && numerator <= denominator).then_some(Self { numerator, denominator })
+    }
+
+    pub fn numerator(&self) -> u64 { 
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u64 { 
+        self.denominator
+    }
+
+    /// whether `voted_stake >= numerator / denominator * threshold_stake`. 
+    /// compared in integers (`denominator * voted_stake >= numerator * 
+    /// threshold_stake`), so is exact at the boundary: this is the 
+    /// authoritative check, `stake_ratio` is only informational. never met 
+    /// without any threshold stake, eg. when no stake could be fetched
+    pub fn is_met(&self, voted_stake: u128, threshold_stake: u128) -> bool { 
+        threshold_stake > 0
+            && u128::from(self.denominator) * voted_stake >= u128::from(self.numerator) * threshold_stake
+    }
+}
+
+impl fmt::Display for SupermajorityThreshold { 
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { 
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl FromStr for SupermajorityThreshold { 
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        let err = || format!("invalid supermajority threshold '{}', expected a fraction like '2/3' in (0, 1]", s);
+        let (numerator, denominator) = s.split_once('/').ok_or_else(err)?;
+        let numerator = numerator.trim().parse().map_err(|_| err())?;
+        let denominator = denominator.trim().parse().map_err(|_| err())?;
+        Self::new(numerator, denominator).ok_or_else(err)
+    }
+}
+
+/// `voted_stake / threshold_stake` as a float, 0 without any threshold 
+/// stake. rounded, so check the threshold with 
+/// `SupermajorityThreshold::is_met` rather than comparing this
+pub fn stake_ratio(voted_stake: u128, threshold_stake: u128) -> f64 { 
+    if threshold_stake == 0 { 
+        return 0.0;
+    }
+    voted_stake as f64 / threshold_stake as f64
+}
+
+/// how close a slot is to final going by the votes for its bank hash, eg. 
+/// for a ui to show progress towards finality before the supermajority
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityConfidence { 
+    /// the bank hash doesnt have a supermajority of the votes (yet). `ratio` 
+    /// is the share of the threshold stake which voted for it so far
+    Unconfirmed { ratio: f64 },
+    /// the bank hash has a supermajority of the votes
+    Confirmed,
+    /// the bank hash has a supermajority and the cluster finalized the slot
+    Finalized,
+}
+
+impl FinalityConfidence { 
+    /// `commitment` is the one the slot reached, if known
+    pub fn new(is_supermajority: bool, voted_stake_ratio: f64, commitment: Option<CommitmentLevel>) -> Self { 
+        match (is_supermajority, commitment) { 
+            (false, _) => FinalityConfidence::Unconfirmed { ratio: voted_stake_ratio },
+            (true, Some(CommitmentLevel::Finalized)) => FinalityConfidence::Finalized,
+            (true, _) => FinalityConfidence::Confirmed,
+        }
+    }
+
+    pub fn is_confirmed(&self) -> bool { 
+        !matches!(self, FinalityConfidence::Unconfirmed { .. })
+    }
+}
+
+impl Default for FinalityConfidence { 
+    fn default() -> Self { 
+        FinalityConfidence::Unconfirmed { ratio: 0.0 }
+    }
+}
+
+impl fmt::Display for FinalityConfidence { 
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { 
+        match self { 
+            FinalityConfidence::Unconfirmed { ratio } => write!(f, "unconfirmed ({:.4})", ratio),
+            FinalityConfidence::Confirmed => write!(f, "confirmed"),
+            FinalityConfidence::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+/// how many slots after a slot are scanned for votes on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteWindow { 
+    /// always scan this many slots
+    Fixed(u64),
+    /// scan until the bank hash has a supermajority of the votes, up to 
+    /// `max_slots`. votes can land several slots later when the network is 
+    /// congested, so this is less likely to undercount than a fixed window
+    Adaptive { max_slots: u64 },
+}
+
+impl Default for VoteWindow { 
+    fn default() -> Self { 
+        VoteWindow::Fixed(5)
+    }
+}
+
+/// the running stake of a vote scan after `slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteProgress { 
+    pub slot: u64,
+    pub slots_scanned: u64,
+    /// stake which voted for the bank hash so far
+    pub voted_stake: u128,
+    /// stake the supermajority is checked against
+    pub threshold_stake: u128,
+    pub total_stake: u128,
+    pub supermajority_threshold: SupermajorityThreshold,
+}
+
+impl VoteProgress { 
+    pub fn is_supermajority(&self) -> bool { 
+        self.supermajority_threshold.is_met(self.voted_stake, self.threshold_stake)
+    }
+
+    /// the confidence in the slot so far, `Confirmed` at most as the scan 
+    /// doesnt know the slot's commitment
+    pub fn confidence(&self) -> FinalityConfidence { 
+        FinalityConfidence::new(self.is_supermajority(), stake_ratio(self.voted_stake, self.threshold_stake), None)
+    }
+}
+
+impl VoteTally { 
+    /// stake which voted for `bank_hash`
+    pub fn stake_for(&self, bank_hash: &Hash) -> u128 { 
+        bank_hash_stake(&self.per_bank_hash, bank_hash)
+    }
+
+    /// the bank hashes with at least 1/20 of the total stake when there is 
+    /// more than one of them (a fork or equivocation), most stake first. 
+    /// empty when the votes agree
+    pub fn competing_bank_hashes(&self) -> Vec<(Hash, u128)> { 
+        let min_stake = self.total_stake / COMPETING_STAKE_DIVISOR;
+        let mut bank_hashes = self.per_bank_hash
+            .keys()
+            .map(|bank_hash| (*bank_hash, self.stake_for(bank_hash)))
+            .filter(|(_, stake)| *stake > 0 && *stake >= min_stake)
+            .collect::<Vec<_>>();
+        if bank_hashes.len() < 2 { 
+            return vec![];
+        }
+        bank_hashes.sort_by_key(|(_, stake)| std::cmp::Reverse(*stake));
+        bank_hashes
+    }
+
+    /// stake which voted on the slot, for any bank hash. compared to 
+    /// `total_stake` it shows how much of the cluster's votes the scan saw
+    pub fn collected_stake(&self) -> u128 { 
+        self.per_bank_hash.keys().map(|bank_hash| self.stake_for(bank_hash)).sum()
+    }
+
+    /// the bank hash with the most stake, if any votes were found
+    pub fn winner(&self) -> Option<Hash> { 
+        self.per_bank_hash
+            .keys()
+            .max_by_key(|bank_hash| self.stake_for(bank_hash))
+            .copied()
+    }
+
+    /// whether the stakes are from a later epoch than the slot
+    pub fn is_stake_approximate(&self) -> bool { 
+        self.stake_epoch != self.slot_epoch
+    }
+
+    /// the stake the supermajority threshold is applied against
+    pub fn threshold_stake(&self, supermajority_stake: SupermajorityStake) -> u128 { 
+        match supermajority_stake { 
+            SupermajorityStake::Total => self.total_stake,
+            SupermajorityStake::Current => self.current_total_stake,
+        }
+    }
+}
+
+/// tally `votes` on `target_slot`, eg. vote transactions streamed from 
+/// geyser or gossip rather than fetched with `getBlock`. votes with invalid 
+/// signatures are dropped and `stake_map` is keyed by vote account. the 
+/// account keys of the transactions must all be static (vote transactions 
+/// dont use lookup tables), and only instructions of `vote_program_id` are 
+/// votes (`solana_sdk::vote::program::id()` unless the cluster has its own, 
+/// see `LightClient::with_vote_program_id`). the stakes in `stake_map` are 
+/// taken as current, and the epochs, skipped and scanned slots are left for 
+/// the caller to fill
+pub fn compute_vote_tally(votes: &[VersionedTransaction], stake_map: &HashMap<Pubkey, u64>, target_slot: u64, vote_program_id: &Pubkey) -> VoteTally { 
+    let vote_candidates = votes
+        .iter()
+        .filter_map(|tx| vote_candidate(tx.clone(), tx.message.static_account_keys(), target_slot, vote_program_id))
+        .collect::<Vec<_>>();
+    let verified = verify_vote_candidates(vote_candidates.iter());
+    let mut vote_counter = VoteCounter::default();
+    let mut signature_verification_failures = vec![];
+    for (candidate, verified) in vote_candidates.iter().zip(verified) { 
+        if verified { 
+            vote_counter.add(candidate, stake_map);
+        } else { 
+            signature_verification_failures.push(candidate.vote_account);
+        }
+    }
+
+    let total_stake = stake_map.values().map(|stake| u128::from(*stake)).sum();
+    VoteTally { 
+        slot: target_slot,
+        slot_epoch: 0,
+        stake_epoch: 0,
+        total_stake,
+        current_total_stake: total_stake,
+        delinquent_stake: 0,
+        per_bank_hash: vote_counter.per_bank_hash,
+        unattributed_votes: vote_counter.unattributed_votes,
+        signature_verification_failures,
+        skipped_slots: vec![],
+        unavailable_slots: vec![],
+        slots_scanned: 0,
+    }
+}
+
+// a vote which passed the scan filters, pending signature verification
+pub(crate) struct VoteCandidate { 
+    bank_hash: Hash,
+    pub(crate) vote_account: Pubkey,
+    message: Vec<u8>,
+    signers: Vec<Pubkey>,
+    signatures: Vec<Signature>,
+}
+
+// the vote on `target_slot` in `tx`, if it has one. `account_keys` are the 
+// tx's account keys, including any loaded from lookup tables
+pub(crate) fn vote_candidate(tx: VersionedTransaction, account_keys: &[Pubkey], target_slot: u64, vote_program_id: &Pubkey) -> Option<VoteCandidate> { 
+    // the bank hash is for the last voted slot, so only votes 
+    // on the target slot say anything about its bank hash
+    match vote_candidate_with_slot(tx, account_keys, vote_program_id)? { 
+        (voted_slot, candidate) if voted_slot == target_slot => Some(candidate),
+        _ => None,
+    }
+}
+
+// the vote in `tx` and the slot its bank hash is for (see `voted_slot`), if 
+// it has one
+pub(crate) fn vote_candidate_with_slot(tx: VersionedTransaction, account_keys: &[Pubkey], vote_program_id: &Pubkey) -> Option<(u64, VoteCandidate)> { 
+    if !account_keys.contains(vote_program_id) { 
+        return None;
+    }
+    // every signer the message requires must have signed it, and a 
+    // transaction without any signatures proves nothing
+    let num_required_signatures = tx.message.header().num_required_signatures as usize;
+    if tx.signatures.is_empty() 
+        || tx.signatures.len() != num_required_signatures 
+        || tx.message.static_account_keys().len() < num_required_signatures { 
+        return None;
+    }
+
+    let msg = tx.message;
+    let ix = msg.instructions()
+        .iter()
+        .find(|ix| account_keys.get(ix.program_id_index as usize) == Some(vote_program_id))?;
+    let vote_ix: VoteInstruction = bincode::deserialize(&ix.data[..]).ok()?;
+    let bank_hash = vote_bank_hash(&vote_ix)?;
+    let voted_slot = voted_slot(&vote_ix)?;
+
+    // the first account of a vote instruction is the vote account
+    let vote_account = *ix.accounts.first().and_then(|i| account_keys.get(*i as usize))?;
+
+    Some((voted_slot, VoteCandidate { 
+        bank_hash,
+        vote_account,
+        // the signed bytes: `VersionedMessage::serialize` prefixes v0 
+        // messages with their version byte and leaves legacy ones as is, 
+        // as `VersionedTransaction::verify_and_hash_message` does
+        message: msg.serialize(),
+        signers: msg.static_account_keys()[..num_required_signatures].to_vec(),
+        signatures: tx.signatures,
+    }))
+}
+
+// whether all the signatures of each candidate verify, checked in one batch
+pub(crate) fn verify_vote_candidates<'a>(vote_candidates: impl Iterator<Item = &'a VoteCandidate> + Clone) -> Vec<bool> { 
+    let mut pubkeys = vec![];
+    let mut messages = vec![];
+    let mut signatures = vec![];
+    for candidate in vote_candidates.clone() { 
+        for (signature, pubkey) in candidate.signatures.iter().zip(candidate.signers.iter()) { 
+            pubkeys.push(*pubkey);
+            messages.push(&candidate.message[..]);
+            signatures.push(*signature);
+        }
+    }
+    let mut verified = verify_signatures(&pubkeys, &messages, &signatures).into_iter();
+    vote_candidates
+        .map(|candidate| { 
+            let n_signatures = candidate.signatures.len().min(candidate.signers.len());
+            let sig_verifies = verified.by_ref().take(n_signatures).collect::<Vec<_>>();
+            // all() is true for no signatures at all
+            !sig_verifies.is_empty() && sig_verifies.into_iter().all(|x| x)
+        })
+        .collect()
+}
+
+// stake-weighted tally of verified votes, built up one vote at a time
+#[derive(Default)]
+pub(crate) struct VoteCounter { 
+    pub(crate) per_bank_hash: HashMap<Hash, Vec<(Pubkey, u64)>>,
+    // a validator can vote for the same bank hash in multiple scanned slots
+    voters: HashMap<Hash, HashSet<Pubkey>>,
+    /// votes from vote accounts with no known stake
+    pub(crate) unattributed_votes: usize,
+}
+
+impl VoteCounter { 
+    pub(crate) fn add(&mut self, candidate: &VoteCandidate, stakes: &HashMap<Pubkey, u64>) { 
+        let vote_account = candidate.vote_account;
+        if !self.voters.entry(candidate.bank_hash).or_default().insert(vote_account) { 
+            return;
+        }
+        // eg. a validator which appeared after the vote accounts were fetched
+        let stake_amount = match stakes.get(&vote_account) { 
+            Some(stake_amount) => stake_amount,
+            None => { 
+                warn!(%vote_account, "no stake found for vote account");
+                self.unattributed_votes += 1;
+                return;
+            }
+        };
+
+        self.per_bank_hash.entry(candidate.bank_hash).or_default().push((vote_account, *stake_amount));
+    }
+}
+
+// stake which voted for `bank_hash`
+pub(crate) fn bank_hash_stake(per_bank_hash: &HashMap<Hash, Vec<(Pubkey, u64)>>, bank_hash: &Hash) -> u128 { 
+    per_bank_hash
+        .get(bank_hash)
+        .map(|voters| voters.iter().map(|(_, stake)| u128::from(*stake)).sum())
+        .unwrap_or_default()
+}
+
+/// the bank hash a vote instruction votes for, if it carries one
+pub fn vote_bank_hash(vote_ix: &VoteInstruction) -> Option<Hash> { 
+    match vote_ix { 
+        VoteInstruction::Vote(v) | VoteInstruction::VoteSwitch(v, _) => Some(v.hash),
+        VoteInstruction::UpdateVoteState(v) 
+        | VoteInstruction::UpdateVoteStateSwitch(v, _) 
+        | VoteInstruction::CompactUpdateVoteState(v) 
+        | VoteInstruction::CompactUpdateVoteStateSwitch(v, _) => Some(v.hash),
+        _ => None
+    }
+}
+
+/// the slot a vote instruction's bank hash is for: the highest slot it votes 
+/// on, from `slots` of a `Vote` or the lockouts of a (compact) vote state 
+/// update. the root isnt a fallback, as the bank hash isnt for it. unlike 
+/// `VoteInstruction::last_voted_slot` it doesnt panic on other instructions, 
+/// which are `None`. (`TowerSync` isnt a variant in this solana version)
+pub fn voted_slot(vote_ix: &VoteInstruction) -> Option<u64> { 
+    match vote_ix { 
+        VoteInstruction::Vote(v) | VoteInstruction::VoteSwitch(v, _) => v.slots.iter().max().copied(),
+        VoteInstruction::UpdateVoteState(v) 
+        | VoteInstruction::UpdateVoteStateSwitch(v, _) 
+        | VoteInstruction::CompactUpdateVoteState(v) 
+        | VoteInstruction::CompactUpdateVoteStateSwitch(v, _) => v.lockouts.iter().map(|lockout| lockout.slot()).max(),
+        _ => None
+    }
+}
+
+/// verify `signatures[i]` of `messages[i]` by `pubkeys[i]`. all signatures 
+/// are checked in a single ed25519 batch, and only if the batch fails are 
+/// they checked one at a time to find which ones are invalid. batch 
+/// verification needs a random number generator, so without the `native` 
+/// feature (eg. on wasm32) they are always checked one at a time
+pub fn verify_signatures(pubkeys: &[Pubkey], messages: &[&[u8]], signatures: &[Signature]) -> Vec<bool> { 
+    if signatures.is_empty() { 
+        return vec![];
+    }
+    if batch_verify(pubkeys, messages, signatures) { 
+        return vec![true; signatures.len()];
+    }
+
+    signatures
+        .iter()
+        .zip(pubkeys.iter())
+        .zip(messages.iter())
+        .map(|((signature, pubkey), message)| signature.verify(pubkey.as_ref(), message))
+        .collect()
+}
+
+#[cfg(feature = "native")]
+fn batch_verify(pubkeys: &[Pubkey], messages: &[&[u8]], signatures: &[Signature]) -> bool { 
+    let pubkeys = pubkeys
+        .iter()
+        .map(|pubkey| ed25519_dalek::PublicKey::from_bytes(pubkey.as_ref()))
+        .collect::<Result<Vec<_>, _>>();
+    let signatures = signatures
+        .iter()
+        .map(|signature| ed25519_dalek::Signature::try_from(signature.as_ref()))
+        .collect::<Result<Vec<_>, _>>();
+    match (pubkeys, signatures) { 
+        (Ok(pubkeys), Ok(signatures)) => ed25519_dalek::verify_batch(messages, &signatures, &pubkeys).is_ok(),
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "native"))]
+fn batch_verify(_pubkeys: &[Pubkey], _messages: &[&[u8]], _signatures: &[Signature]) -> bool { 
+    false
+}
+
+#[cfg(test)]
+mod tests { 
+    use solana_sdk::{message::{v0, VersionedMessage}, signature::Keypair, signer::Signer, transaction::Transaction, vote::{instruction::vote, program as vote_program, state::{Vote, VoteStateUpdate}}};
+
+    use super::*;
+
+    // a vote for `bank_hash` on `slot` by `vote_account`, signed by its 
+    // authorized voter
+    fn vote_tx(vote_account: &Pubkey, authorized_voter: &Keypair, slot: u64, bank_hash: Hash) -> VersionedTransaction { 
+        let ix = vote(vote_account, &authorized_voter.pubkey(), Vote::new(vec![slot], bank_hash));
+        Transaction::new_signed_with_payer(&[ix], Some(&authorized_voter.pubkey()), &[authorized_voter], Hash::new_unique()).into()
+    }
+
+    #[test]
+    fn test_stake_is_attributed_to_the_vote_account() { 
+        let (vote_account, authorized_voter) = (Pubkey::new_unique(), Keypair::new());
+        let bank_hash = Hash::new_unique();
+        let votes = [vote_tx(&vote_account, &authorized_voter, 10, bank_hash)];
+
+        let stakes = HashMap::from([(vote_account, 100)]);
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+        assert_eq!(vote_tally.per_bank_hash.get(&bank_hash), Some(&vec![(vote_account, 100)]));
+        assert_eq!(vote_tally.unattributed_votes, 0);
+
+        // the signer isnt the vote account, so its stake doesnt count
+        let stakes = HashMap::from([(authorized_voter.pubkey(), 100)]);
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+        assert_eq!(vote_tally.stake_for(&bank_hash), 0);
+        assert_eq!(vote_tally.unattributed_votes, 1);
+
+        // a cluster with its own vote program doesnt count these
+        let stakes = HashMap::from([(vote_account, 100)]);
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &Pubkey::new_unique());
+        assert_eq!(vote_tally.collected_stake(), 0);
+    }
+
+    #[test]
+    fn test_repeated_votes_are_counted_once() { 
+        let (validator, other_validator) = (Keypair::new(), Keypair::new());
+        let bank_hash = Hash::new_unique();
+        // the same vote landing in two blocks
+        let votes = [
+            vote_tx(&validator.pubkey(), &validator, 10, bank_hash),
+            vote_tx(&validator.pubkey(), &validator, 10, bank_hash),
+            vote_tx(&other_validator.pubkey(), &other_validator, 10, bank_hash),
+        ];
+        let stakes = HashMap::from([(validator.pubkey(), 100), (other_validator.pubkey(), 50)]);
+
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+        assert_eq!(vote_tally.per_bank_hash.get(&bank_hash).map(Vec::len), Some(2));
+        assert_eq!(vote_tally.stake_for(&bank_hash), 150);
+    }
+
+    // one of each vote instruction variant for `bank_hash`, voting on slots 8 
+    // and 10
+    fn vote_instructions(bank_hash: Hash) -> Vec<VoteInstruction> { 
+        let vote = Vote::new(vec![8, 10], bank_hash);
+        let mut vote_state_update = VoteStateUpdate::from(vec![(8, 2), (10, 1)]);
+        vote_state_update.hash = bank_hash;
+        let proof_hash = Hash::new_unique();
+        vec![
+            VoteInstruction::Vote(vote.clone()),
+            VoteInstruction::VoteSwitch(vote, proof_hash),
+            VoteInstruction::UpdateVoteState(vote_state_update.clone()),
+            VoteInstruction::UpdateVoteStateSwitch(vote_state_update.clone(), proof_hash),
+            VoteInstruction::CompactUpdateVoteState(vote_state_update.clone()),
+            VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, proof_hash),
+        ]
+    }
+
+    #[test]
+    fn test_vote_bank_hash() { 
+        let bank_hash = Hash::new_unique();
+        for vote_ix in vote_instructions(bank_hash) { 
+            // as the instruction data is decoded in a block
+            let vote_ix: VoteInstruction = bincode::deserialize(&bincode::serialize(&vote_ix).unwrap()).unwrap();
+            assert_eq!(vote_bank_hash(&vote_ix), Some(bank_hash), "{:?}", vote_ix);
+        }
+        assert_eq!(vote_bank_hash(&VoteInstruction::Withdraw(1)), None);
+    }
+
+    #[test]
+    fn test_votes_without_stake_are_unattributed() { 
+        let (validator, unknown_validator) = (Keypair::new(), Keypair::new());
+        let bank_hash = Hash::new_unique();
+        let votes = [
+            vote_tx(&validator.pubkey(), &validator, 10, bank_hash),
+            vote_tx(&unknown_validator.pubkey(), &unknown_validator, 10, bank_hash),
+        ];
+        let stakes = HashMap::from([(validator.pubkey(), 100)]);
+
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+        assert_eq!(vote_tally.unattributed_votes, 1);
+        assert_eq!(vote_tally.per_bank_hash.get(&bank_hash), Some(&vec![(validator.pubkey(), 100)]));
+        assert!(vote_tally.signature_verification_failures.is_empty());
+    }
+
+    #[test]
+    fn test_unsigned_vote_is_rejected() { 
+        let validator = Keypair::new();
+        let bank_hash = Hash::new_unique();
+        let mut tx = vote_tx(&validator.pubkey(), &validator, 10, bank_hash);
+        tx.signatures.clear();
+        let stakes = HashMap::from([(validator.pubkey(), 100)]);
+
+        let vote_tally = compute_vote_tally(&[tx], &stakes, 10, &vote_program::id());
+        assert!(vote_tally.per_bank_hash.is_empty());
+        assert_eq!(vote_tally.unattributed_votes, 0);
+        assert!(vote_tally.signature_verification_failures.is_empty());
+    }
+
+    #[test]
+    fn test_tampered_signature_is_reported() { 
+        let (validator, tampered_validator) = (Keypair::new(), Keypair::new());
+        let bank_hash = Hash::new_unique();
+        let mut tampered_tx = vote_tx(&tampered_validator.pubkey(), &tampered_validator, 10, bank_hash);
+        tampered_tx.signatures[0] = Signature::new_unique();
+        let votes = [vote_tx(&validator.pubkey(), &validator, 10, bank_hash), tampered_tx];
+        let stakes = HashMap::from([(validator.pubkey(), 100), (tampered_validator.pubkey(), 50)]);
+
+        let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+        assert_eq!(vote_tally.signature_verification_failures, vec![tampered_validator.pubkey()]);
+        assert_eq!(vote_tally.stake_for(&bank_hash), 100);
+    }
+
+    #[test]
+    fn test_supermajority_near_u64_overflow() { 
+        let max_stake = u128::from(u64::MAX);
+        let per_bank_hash = HashMap::from([(Hash::default(), vec![(Pubkey::new_unique(), u64::MAX), (Pubkey::new_unique(), u64::MAX)])]);
+        let voted_stake = bank_hash_stake(&per_bank_hash, &Hash::default());
+        assert_eq!(voted_stake, 2 * max_stake);
+
+        // 3 * total stake is past u64::MAX
+        let threshold = SupermajorityThreshold::default();
+        let threshold_stake = 3 * max_stake;
+        assert!(threshold.is_met(voted_stake, threshold_stake));
+        assert!(!threshold.is_met(voted_stake - 1, threshold_stake));
+        assert!(!threshold.is_met(voted_stake, threshold_stake + 1));
+    }
+
+    #[test]
+    fn test_v0_vote_signature_verifies() { 
+        let validator = Keypair::new();
+        let bank_hash = Hash::new_unique();
+        let ix = vote(&validator.pubkey(), &validator.pubkey(), Vote::new(vec![10], bank_hash));
+        let message = v0::Message::try_compile(&validator.pubkey(), &[ix], &[], Hash::new_unique()).unwrap();
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&validator]).unwrap();
+        let stakes = HashMap::from([(validator.pubkey(), 100)]);
+
+        let vote_tally = compute_vote_tally(&[tx], &stakes, 10, &vote_program::id());
+        assert!(vote_tally.signature_verification_failures.is_empty());
+        assert_eq!(vote_tally.stake_for(&bank_hash), 100);
+    }
+
+    #[test]
+    fn test_voted_slot() { 
+        for vote_ix in vote_instructions(Hash::new_unique()) { 
+            let vote_ix: VoteInstruction = bincode::deserialize(&bincode::serialize(&vote_ix).unwrap()).unwrap();
+            assert_eq!(voted_slot(&vote_ix), Some(10), "{:?}", vote_ix);
+        }
+
+        // the root isnt voted on
+        let mut vote_state_update = VoteStateUpdate::from(vec![]);
+        vote_state_update.root = Some(7);
+        assert_eq!(voted_slot(&VoteInstruction::CompactUpdateVoteState(vote_state_update)), None);
+        assert_eq!(voted_slot(&VoteInstruction::Vote(Vote::new(vec![], Hash::new_unique()))), None);
+        assert_eq!(voted_slot(&VoteInstruction::Withdraw(1)), None);
+    }
+
+    #[test]
+    fn test_supermajority_threshold_parsing() { 
+        assert_eq!("2/3".parse(), Ok(SupermajorityThreshold::default()));
+        assert_eq!(" 3 / 4 ".trim().parse(), Ok(SupermajorityThreshold::new(3, 4).unwrap()));
+        assert_eq!("1/1".parse::<SupermajorityThreshold>().map(|x| x.to_string()), Ok("1/1".to_string()));
+        for invalid in ["0/3", "4/3", "2/0", "2", "2/3/4", "a/b"] { 
+            assert!(invalid.parse::<SupermajorityThreshold>().is_err(), "{}", invalid);
+        }
+
+        // exact at the boundary, where the float ratio would round
+        let threshold = SupermajorityThreshold::default();
+        assert!(threshold.is_met(200, 300));
+        assert!(!threshold.is_met(199, 300));
+    }
+
+    #[test]
+    fn test_supermajority_needs_threshold_stake() { 
+        // without any stake 0 >= 2/3 * 0 would hold
+        assert!(!SupermajorityThreshold::default().is_met(0, 0));
+        assert!(!SupermajorityThreshold::new(1, 1).unwrap().is_met(0, 0));
+
+        let vote_tally = compute_vote_tally(&[], &HashMap::new(), 10, &vote_program::id());
+        let progress = VoteProgress { 
+            slot: 10,
+            slots_scanned: 1,
+            voted_stake: vote_tally.stake_for(&Hash::default()),
+            threshold_stake: vote_tally.threshold_stake(SupermajorityStake::Total),
+            total_stake: vote_tally.total_stake,
+            supermajority_threshold: SupermajorityThreshold::default(),
+        };
+        assert!(!progress.is_supermajority());
+    }
+}
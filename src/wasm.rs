@@ -0,0 +1,185 @@
+use std::{collections::HashMap, str::FromStr, sync::atomic::{AtomicU64, Ordering}};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{de::DeserializeOwned, Deserialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use tracing::debug;
+
+use crate::error::LightNodeError;
+use crate::votes::{compute_vote_tally, VoteTally};
+
+// skipped slot and slot missing in long-term storage, as `LightClient::get_block`
+const SLOT_SKIPPED_ERROR_CODES: [i64; 2] = [-32007, -32009];
+
+/// json-rpc over reqwest without a tokio runtime, eg. in the browser where 
+/// reqwest uses fetch. the rpc client and `HttpTransport` dont build for 
+/// wasm32, so this covers what the verification without the `native` 
+/// feature needs: the vote account stakes and the vote transactions of 
+/// blocks, to tally with `compute_vote_tally`. blocks are decoded without 
+/// solana-transaction-status, and the block headers are returned as the 
+/// raw bincode for `verify::deserialize_block_header` (`block-headers` 
+/// feature)
+pub struct WasmTransport { 
+    endpoint: String,
+    http_client: reqwest::Client,
+    next_request_id: AtomicU64,
+    vote_program_id: Pubkey,
+}
+
+#[derive(Deserialize)]
+struct RawResponse<T> { 
+    result: Option<T>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError { 
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RawVersion { 
+    #[serde(rename = "solana-core")]
+    solana_core: String,
+}
+
+#[derive(Deserialize)]
+struct RawVoteAccounts { 
+    current: Vec<RawVoteAccount>,
+    delinquent: Vec<RawVoteAccount>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawVoteAccount { 
+    vote_pubkey: String,
+    activated_stake: u64,
+}
+
+// a base64 encoded `getBlock` result, only the transactions are decoded
+#[derive(Deserialize)]
+struct RawBlock { 
+    transactions: Vec<RawBlockTransaction>,
+}
+
+#[derive(Deserialize)]
+struct RawBlockTransaction { 
+    // the encoded transaction and its encoding
+    transaction: (String, String),
+}
+
+impl WasmTransport { 
+    pub fn new(endpoint: impl Into<String>) -> Self { 
+        Self { 
+            endpoint: endpoint.into(),
+            http_client: reqwest::Client::new(),
+            next_request_id: AtomicU64::new(0),
+            vote_program_id: solana_sdk::vote::program::id(),
+        }
+    }
+
+    pub fn endpoint(&self) -> &str { 
+        &self.endpoint
+    }
+
+    /// program whose instructions are tallied as votes, defaults to the vote 
+    /// program, as `LightClient::with_vote_program_id`
+    pub fn with_vote_program_id(mut self, vote_program_id: Pubkey) -> Self { 
+        self.vote_program_id = vote_program_id;
+        self
+    }
+
+    pub fn vote_program_id(&self) -> &Pubkey { 
+        &self.vote_program_id
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: serde_json::Value) -> Result<T, LightNodeError> { 
+        let request = serde_json::json!({ 
+            "jsonrpc": "2.0",
+            "id": self.next_request_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        }).to_string();
+        let resp = self.http_client
+            .post(&self.endpoint)
+            .body(request)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .send()
+            .await?
+            .text()
+            .await?;
+        match serde_json::from_str::<RawResponse<T>>(&resp)? { 
+            RawResponse { error: Some(error), .. } => Err(LightNodeError::JsonRpc { code: error.code, message: error.message }),
+            RawResponse { result: Some(result), .. } => Ok(result),
+            RawResponse { result: None, .. } => Err(LightNodeError::NullResult { method: method.to_string() }),
+        }
+    }
+
+    /// the cluster's `solana-core` version
+    pub async fn get_version(&self) -> Result<String, LightNodeError> { 
+        Ok(self.call::<RawVersion>("getVersion", serde_json::json!([])).await?.solana_core)
+    }
+
+    /// activated stake of the current and delinquent vote accounts, keyed by 
+    /// vote account as `compute_vote_tally` takes it
+    pub async fn get_vote_account_stakes(&self) -> Result<HashMap<Pubkey, u64>, LightNodeError> { 
+        let vote_accounts: RawVoteAccounts = self.call("getVoteAccounts", serde_json::json!([{ "commitment": "confirmed" }])).await?;
+        vote_accounts.current
+            .iter()
+            .chain(vote_accounts.delinquent.iter())
+            .map(|x| Ok((Pubkey::from_str(&x.vote_pubkey)?, x.activated_stake)))
+            .collect()
+    }
+
+    /// the transactions of the confirmed block in `slot`. a skipped slot 
+    /// fails with `SlotSkipped`
+    pub async fn get_block_transactions(&self, slot: u64) -> Result<Vec<VersionedTransaction>, LightNodeError> { 
+        let config = serde_json::json!({ 
+            "encoding": "base64",
+            "transactionDetails": "full",
+            "rewards": false,
+            "maxSupportedTransactionVersion": 0,
+            "commitment": "confirmed",
+        });
+        let block: RawBlock = match self.call("getBlock", serde_json::json!([slot, config])).await { 
+            Err(LightNodeError::JsonRpc { code, message }) if SLOT_SKIPPED_ERROR_CODES.contains(&code) => { 
+                return Err(LightNodeError::SlotSkipped { slot, code, message });
+            }
+            result => result?,
+        };
+        block.transactions
+            .iter()
+            .map(|x| Ok(bincode::deserialize(&BASE64.decode(&x.transaction.0)?)?))
+            .collect()
+    }
+
+    /// the bincode serialized `BlockHeader` of `slot`, see 
+    /// `verify::deserialize_block_header`
+    pub async fn get_block_headers(&self, slot: u64, signature: Signature) -> Result<Vec<u8>, LightNodeError> { 
+        self.call("getBlockHeaders", serde_json::json!([slot, signature.as_ref()])).await
+    }
+
+    /// tally the votes on `target_slot` in the blocks of the `slots_ahead` 
+    /// slots from it on with the current stakes, as `compute_vote_tally`. 
+    /// skipped slots are recorded in the tally
+    pub async fn vote_tally(&self, target_slot: u64, slots_ahead: u64) -> Result<VoteTally, LightNodeError> { 
+        let stakes = self.get_vote_account_stakes().await?;
+        let mut votes = vec![];
+        let mut skipped_slots = vec![];
+        for slot in target_slot..target_slot + slots_ahead.max(1) { 
+            match self.get_block_transactions(slot).await { 
+                Ok(transactions) => votes.extend(transactions),
+                Err(LightNodeError::SlotSkipped { .. }) => { 
+                    debug!(slot, "slot skipped");
+                    skipped_slots.push(slot);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let mut vote_tally = compute_vote_tally(&votes, &stakes, target_slot, &self.vote_program_id);
+        vote_tally.skipped_slots = skipped_slots;
+        vote_tally.slots_scanned = slots_ahead.max(1);
+        Ok(vote_tally)
+    }
+}
@@ -0,0 +1,61 @@
+//! the verification without the `native` feature, run in node with 
+//! `wasm-pack test --node -- --no-default-features --features wasm --test wasm`
+#![cfg(target_arch = "wasm32")]
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use solana_sdk::{
+    hash::{hash, hashv, Hash},
+    signature::{keypair_from_seed, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+    vote::{instruction::vote, program as vote_program, state::Vote},
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use vote::bank_hash::recompute_bank_hash;
+use vote::merkle::transaction_leaf_hash;
+use vote::poh::next_hash_with_tx_hash;
+use vote::votes::compute_vote_tally;
+
+#[wasm_bindgen_test]
+fn test_next_hash_with_tx_hash() { 
+    let start_hash = Hash::new_from_array([1; 32]);
+    let transaction_hash = transaction_leaf_hash(&Signature::from([2; 64]));
+    assert_eq!(next_hash_with_tx_hash(&start_hash, 2, None), hash(hash(start_hash.as_ref()).as_ref()));
+    assert_eq!(
+        next_hash_with_tx_hash(&start_hash, 2, Some(transaction_hash)),
+        hashv(&[hash(start_hash.as_ref()).as_ref(), transaction_hash.as_ref()])
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_recompute_bank_hash() { 
+    let [parent_hash, accounts_delta_hash, last_blockhash, epoch_accounts_hash] = [1, 2, 3, 4].map(|x| Hash::new_from_array([x; 32]));
+    // the same golden hashes as the native unit test
+    assert_eq!(
+        recompute_bank_hash(&parent_hash, &accounts_delta_hash, 5, &last_blockhash, None),
+        Hash::from_str("2SWvpdZttMinTTpnTaiD3ezXLJbaVHXjB4DHC35Gp2dT").unwrap()
+    );
+    assert_eq!(
+        recompute_bank_hash(&parent_hash, &accounts_delta_hash, 5, &last_blockhash, Some(epoch_accounts_hash)),
+        Hash::from_str("6f9nhANSHj7gV8AaCinicB3AFbmYMysS29oSggAJTwA7").unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_compute_vote_tally() { 
+    // seeded, so no rng is needed in the browser
+    let validator = keypair_from_seed(&[1; 32]).unwrap();
+    let bank_hash = Hash::new_from_array([5; 32]);
+    let ix = vote(&validator.pubkey(), &validator.pubkey(), Vote::new(vec![10], bank_hash));
+    let votes: Vec<VersionedTransaction> = vec![
+        Transaction::new_signed_with_payer(&[ix], Some(&validator.pubkey()), &[&validator], Hash::new_from_array([6; 32])).into(),
+    ];
+
+    let stakes = HashMap::from([(validator.pubkey(), 100)]);
+    let vote_tally = compute_vote_tally(&votes, &stakes, 10, &vote_program::id());
+    assert_eq!(vote_tally.stake_for(&bank_hash), 100);
+    assert!(vote_tally.signature_verification_failures.is_empty());
+}